@@ -3,6 +3,7 @@ use futures_util::StreamExt;
 use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use reqwest_eventsource::{Event, EventSource};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 const API_URL: &str = "https://api.anthropic.com/v1/messages";
 const API_VERSION: &str = "2023-06-01";
@@ -52,12 +53,41 @@ pub enum ContentBlock {
     },
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct Usage {
+    #[serde(default)]
     pub input_tokens: u32,
+    #[serde(default)]
     pub output_tokens: u32,
 }
 
+/// A structured streaming event, emitted as the SSE stream is parsed
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    TextDelta(String),
+    ToolUseStarted { id: String, name: String },
+    ToolUseCompleted {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    Done {
+        stop_reason: Option<String>,
+        usage: Option<Usage>,
+    },
+}
+
+/// Per-block accumulation state while a stream is in flight, keyed by the
+/// content block's index.
+enum StreamBlock {
+    Text,
+    ToolUse {
+        id: String,
+        name: String,
+        partial_json: String,
+    },
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct Tool {
     pub name: String,
@@ -65,6 +95,14 @@ pub struct Tool {
     pub input_schema: serde_json::Value,
 }
 
+/// A single tool call made during an agent loop, recorded for the caller
+#[derive(Debug, Clone)]
+pub struct ToolInvocation {
+    pub name: String,
+    pub input: serde_json::Value,
+    pub result: String,
+}
+
 pub struct ClaudeClient {
     client: reqwest::Client,
     api_key: String,
@@ -145,56 +183,112 @@ impl ClaudeClient {
         Ok(resp.json().await?)
     }
 
-    /// Non-streaming call with tools
-    pub async fn send_with_tools(
+    /// Drive the full multi-turn tool-use cycle over the streaming API:
+    /// each turn is sent via `stream`, so `on_text` sees text deltas as
+    /// Claude produces them instead of waiting on the full response.
+    /// Whenever a turn stops with `tool_use`, run `handler` for every
+    /// tool call, feed the results back as `tool_result` blocks, and
+    /// resend. Loops until a turn stops for any other reason; after
+    /// `MAX_ITERATIONS` turns it gives up with `AiError::ToolLoopExceeded`.
+    ///
+    /// Returns the final assistant text plus a transcript of every tool
+    /// call made along the way.
+    pub async fn run_agent(
         &self,
-        messages: Vec<Message>,
+        mut messages: Vec<Message>,
         tools: Vec<Tool>,
-    ) -> Result<MessageResponse, AiError> {
-        let request = MessageRequest {
-            model: self.model.clone(),
-            max_tokens: 4096,
-            system: self.system_prompt.clone(),
-            messages,
-            stream: None,
-            tools: Some(tools),
-        };
+        mut on_text: impl FnMut(String) + Send,
+        handler: impl Fn(
+            &str,
+            &serde_json::Value,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = String> + Send>>
+            + Send
+            + Sync,
+    ) -> Result<(String, Vec<ToolInvocation>), AiError> {
+        const MAX_ITERATIONS: u32 = 25;
 
-        let resp = self
-            .client
-            .post(API_URL)
-            .headers(self.headers())
-            .json(&request)
-            .send()
+        let mut transcript = Vec::new();
+
+        for _ in 0..MAX_ITERATIONS {
+            let mut text = String::new();
+            let mut tool_uses: Vec<(String, String, serde_json::Value)> = Vec::new();
+            let mut stop_reason: Option<String> = None;
+
+            self.stream(messages.clone(), Some(tools.clone()), |event| match event {
+                StreamEvent::TextDelta(delta) => {
+                    text.push_str(&delta);
+                    on_text(delta);
+                }
+                StreamEvent::ToolUseCompleted { id, name, input } => {
+                    tool_uses.push((id, name, input));
+                }
+                StreamEvent::Done { stop_reason: reason, .. } => {
+                    stop_reason = reason;
+                }
+                StreamEvent::ToolUseStarted { .. } => {}
+            })
             .await?;
 
-        if !resp.status().is_success() {
-            let status = resp.status().as_u16();
-            if status == 429 {
-                return Err(AiError::RateLimited);
+            if stop_reason.as_deref() != Some("tool_use") {
+                return Ok((text, transcript));
             }
-            let msg = resp.text().await.unwrap_or_default();
-            return Err(AiError::ApiError {
-                status,
-                message: msg,
+
+            let mut content_blocks = Vec::new();
+            if !text.is_empty() {
+                content_blocks.push(serde_json::json!({ "type": "text", "text": text }));
+            }
+
+            let mut tool_results = Vec::new();
+            for (id, name, input) in &tool_uses {
+                content_blocks.push(serde_json::json!({
+                    "type": "tool_use",
+                    "id": id,
+                    "name": name,
+                    "input": input,
+                }));
+
+                let result = handler(name, input).await;
+                transcript.push(ToolInvocation {
+                    name: name.clone(),
+                    input: input.clone(),
+                    result: result.clone(),
+                });
+                tool_results.push(serde_json::json!({
+                    "type": "tool_result",
+                    "tool_use_id": id,
+                    "content": result,
+                }));
+            }
+
+            messages.push(Message {
+                role: "assistant".into(),
+                content: serde_json::Value::Array(content_blocks),
+            });
+            messages.push(Message {
+                role: "user".into(),
+                content: serde_json::Value::Array(tool_results),
             });
         }
 
-        Ok(resp.json().await?)
+        Err(AiError::ToolLoopExceeded)
     }
 
-    /// Streaming call — yields text chunks via callback
+    /// Streaming call — parses the full SSE event set and yields
+    /// `StreamEvent`s via callback, so a UI can render text as it arrives
+    /// while still capturing any tool calls for the agent loop.
     pub async fn stream(
         &self,
-        prompt: &str,
-        mut on_chunk: impl FnMut(String),
+        messages: Vec<Message>,
+        tools: Option<Vec<Tool>>,
+        mut on_event: impl FnMut(StreamEvent),
     ) -> Result<(), AiError> {
         let body = serde_json::json!({
             "model": self.model,
             "max_tokens": 4096,
             "stream": true,
             "system": self.system_prompt,
-            "messages": [{"role": "user", "content": prompt}]
+            "messages": messages,
+            "tools": tools,
         });
 
         let request = self
@@ -208,22 +302,101 @@ impl ClaudeClient {
             message: e.to_string(),
         })?;
 
+        let mut blocks: HashMap<u32, StreamBlock> = HashMap::new();
+        let mut stop_reason: Option<String> = None;
+        let mut usage: Option<Usage> = None;
+
         while let Some(event) = es.next().await {
             match event {
-                Ok(Event::Message(msg)) => match msg.event.as_str() {
-                    "content_block_delta" => {
-                        if let Ok(data) = serde_json::from_str::<serde_json::Value>(&msg.data) {
-                            if let Some(text) = data["delta"]["text"].as_str() {
-                                on_chunk(text.to_string());
+                Ok(Event::Message(msg)) => {
+                    let Ok(data) = serde_json::from_str::<serde_json::Value>(&msg.data) else {
+                        continue;
+                    };
+
+                    match msg.event.as_str() {
+                        "message_start" => {
+                            if let Ok(u) =
+                                serde_json::from_value(data["message"]["usage"].clone())
+                            {
+                                usage = Some(u);
                             }
                         }
+                        "content_block_start" => {
+                            let index = data["index"].as_u64().unwrap_or(0) as u32;
+                            let block = &data["content_block"];
+                            if block["type"].as_str() == Some("tool_use") {
+                                let id = block["id"].as_str().unwrap_or_default().to_string();
+                                let name = block["name"].as_str().unwrap_or_default().to_string();
+                                on_event(StreamEvent::ToolUseStarted {
+                                    id: id.clone(),
+                                    name: name.clone(),
+                                });
+                                blocks.insert(
+                                    index,
+                                    StreamBlock::ToolUse {
+                                        id,
+                                        name,
+                                        partial_json: String::new(),
+                                    },
+                                );
+                            } else {
+                                blocks.insert(index, StreamBlock::Text);
+                            }
+                        }
+                        "content_block_delta" => {
+                            let index = data["index"].as_u64().unwrap_or(0) as u32;
+                            let delta = &data["delta"];
+                            match delta["type"].as_str() {
+                                Some("text_delta") => {
+                                    if let Some(text) = delta["text"].as_str() {
+                                        on_event(StreamEvent::TextDelta(text.to_string()));
+                                    }
+                                }
+                                Some("input_json_delta") => {
+                                    if let Some(partial) = delta["partial_json"].as_str() {
+                                        if let Some(StreamBlock::ToolUse {
+                                            partial_json, ..
+                                        }) = blocks.get_mut(&index)
+                                        {
+                                            partial_json.push_str(partial);
+                                        }
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                        "content_block_stop" => {
+                            let index = data["index"].as_u64().unwrap_or(0) as u32;
+                            if let Some(StreamBlock::ToolUse {
+                                id,
+                                name,
+                                partial_json,
+                            }) = blocks.remove(&index)
+                            {
+                                let input = if partial_json.is_empty() {
+                                    serde_json::Value::Object(Default::default())
+                                } else {
+                                    serde_json::from_str(&partial_json)
+                                        .unwrap_or(serde_json::Value::Null)
+                                };
+                                on_event(StreamEvent::ToolUseCompleted { id, name, input });
+                            }
+                        }
+                        "message_delta" => {
+                            if let Some(reason) = data["delta"]["stop_reason"].as_str() {
+                                stop_reason = Some(reason.to_string());
+                            }
+                            if let Ok(u) = serde_json::from_value(data["usage"].clone()) {
+                                usage = Some(u);
+                            }
+                        }
+                        "message_stop" => {
+                            es.close();
+                            break;
+                        }
+                        _ => {}
                     }
-                    "message_stop" => {
-                        es.close();
-                        break;
-                    }
-                    _ => {}
-                },
+                }
                 Err(_) => {
                     es.close();
                     break;
@@ -232,6 +405,8 @@ impl ClaudeClient {
             }
         }
 
+        on_event(StreamEvent::Done { stop_reason, usage });
+
         Ok(())
     }
 }
@@ -245,7 +420,11 @@ pub fn desktop_tools() -> Vec<Tool> {
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
-                    "app_name": { "type": "string", "description": "Application name" }
+                    "app_name": { "type": "string", "description": "Application name" },
+                    "action_name": {
+                        "type": "string",
+                        "description": "Name of a Desktop Action to launch instead of the app's main entry, e.g. \"New Private Window\""
+                    }
                 },
                 "required": ["app_name"]
             }),
@@ -265,6 +444,23 @@ pub fn desktop_tools() -> Vec<Tool> {
                 "required": ["action"]
             }),
         },
+        Tool {
+            name: "open_with".into(),
+            description: "Open a file with an installed application, optionally choosing \
+                which one instead of the system default handler"
+                .into(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Path of the file to open" },
+                    "app_name": {
+                        "type": "string",
+                        "description": "Name of the application to open it with; defaults to the system handler"
+                    }
+                },
+                "required": ["path"]
+            }),
+        },
     ]
 }
 
@@ -0,0 +1,147 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// Persistent multi-turn conversation store, backed by a local SQLite
+/// database, so the assistant remembers past chats across restarts.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ConversationId(pub i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MessageId(pub i64);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationSummary {
+    pub id: ConversationId,
+    pub title: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredMessage {
+    pub id: MessageId,
+    pub conversation_id: ConversationId,
+    pub role: String,
+    pub content: String,
+    pub model: Option<String>,
+    pub created_at: i64,
+}
+
+impl StoredMessage {
+    /// Convert to the `Message` shape `ClaudeClient` expects
+    pub fn to_claude_message(&self) -> crate::claude::Message {
+        crate::claude::Message {
+            role: self.role.clone(),
+            content: serde_json::Value::String(self.content.clone()),
+        }
+    }
+}
+
+pub struct ConversationStore {
+    conn: Connection,
+}
+
+impl ConversationStore {
+    /// Open (creating if needed) the conversation database under the user
+    /// data dir
+    pub fn open() -> rusqlite::Result<Self> {
+        let path = db_path();
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS conversations (
+                id INTEGER PRIMARY KEY,
+                title TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS messages (
+                id INTEGER PRIMARY KEY,
+                conversation_id INTEGER NOT NULL REFERENCES conversations(id),
+                role TEXT NOT NULL,
+                content TEXT NOT NULL,
+                model TEXT,
+                created_at INTEGER NOT NULL
+            );",
+        )?;
+        Ok(Self { conn })
+    }
+
+    /// Start a new conversation, returning its id
+    pub fn new_conversation(&self, title: &str) -> rusqlite::Result<ConversationId> {
+        self.conn.execute(
+            "INSERT INTO conversations (title, created_at) VALUES (?1, ?2)",
+            params![title, now_unix()],
+        )?;
+        Ok(ConversationId(self.conn.last_insert_rowid()))
+    }
+
+    /// List saved conversations, most recently created first
+    pub fn list_conversations(&self) -> rusqlite::Result<Vec<ConversationSummary>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, title, created_at FROM conversations ORDER BY created_at DESC",
+        )?;
+        stmt.query_map([], |row| {
+            Ok(ConversationSummary {
+                id: ConversationId(row.get(0)?),
+                title: row.get(1)?,
+                created_at: row.get(2)?,
+            })
+        })?
+        .collect()
+    }
+
+    /// Append a turn to a conversation
+    pub fn append_message(
+        &self,
+        conversation_id: ConversationId,
+        role: &str,
+        content: &str,
+        model: Option<&str>,
+    ) -> rusqlite::Result<MessageId> {
+        self.conn.execute(
+            "INSERT INTO messages (conversation_id, role, content, model, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![conversation_id.0, role, content, model, now_unix()],
+        )?;
+        Ok(MessageId(self.conn.last_insert_rowid()))
+    }
+
+    /// Load every message in a conversation, oldest first
+    pub fn load_messages(
+        &self,
+        conversation_id: ConversationId,
+    ) -> rusqlite::Result<Vec<StoredMessage>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, conversation_id, role, content, model, created_at
+             FROM messages WHERE conversation_id = ?1 ORDER BY created_at ASC, id ASC",
+        )?;
+        stmt.query_map(params![conversation_id.0], |row| {
+            Ok(StoredMessage {
+                id: MessageId(row.get(0)?),
+                conversation_id: ConversationId(row.get(1)?),
+                role: row.get(2)?,
+                content: row.get(3)?,
+                model: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?
+        .collect()
+    }
+}
+
+fn db_path() -> std::path::PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("ai-ui")
+        .join("conversations.sqlite3")
+}
+
+fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
@@ -1,7 +1,8 @@
 pub mod claude;
+pub mod history;
 pub mod ollama;
 pub mod mcp;
-pub mod streaming;
+pub mod tokens;
 
 use thiserror::Error;
 
@@ -21,37 +22,63 @@ pub enum AiError {
 
     #[error("No AI backend available")]
     NoBackend,
+
+    #[error("tool '{tool}' failed: {message}")]
+    ToolCallFailed { tool: String, message: String },
+
+    #[error("agent loop exceeded its iteration budget")]
+    ToolLoopExceeded,
 }
 
 /// Unified AI backend — Claude first, Ollama fallback
+///
+/// `history` is the prior turns of the active conversation (oldest first);
+/// it's sent ahead of `prompt` so both backends answer with context, after
+/// the oldest turns are dropped until it fits the model's context window.
+/// `claude_reachable` lets the caller skip the Claude attempt entirely
+/// (e.g. because a connectivity probe already found it unreachable)
+/// instead of waiting on a request to fail or time out.
 pub async fn generate_response(
     prompt: &str,
     claude_key: Option<&str>,
+    history: &[claude::Message],
+    claude_reachable: bool,
 ) -> Result<String, AiError> {
-    // Try Claude first
+    let model = if claude_key.is_some() {
+        "claude-sonnet-4-5-20250929"
+    } else {
+        "llama3.2"
+    };
+
+    let mut messages = history.to_vec();
+    tokens::trim_to_fit(model, &mut messages, prompt);
+    messages.push(claude::Message {
+        role: "user".into(),
+        content: serde_json::Value::String(prompt.into()),
+    });
+
+    // Try Claude first, unless we already know it's unreachable
     if let Some(key) = claude_key {
-        let client = claude::ClaudeClient::new(key.to_string());
-        match client
-            .send(vec![claude::Message {
-                role: "user".into(),
-                content: serde_json::Value::String(prompt.into()),
-            }])
-            .await
-        {
-            Ok(resp) => {
-                for block in &resp.content {
-                    if let claude::ContentBlock::Text { text } = block {
-                        return Ok(text.clone());
+        if claude_reachable {
+            let client = claude::ClaudeClient::new(key.to_string());
+            match client.send(messages.clone()).await {
+                Ok(resp) => {
+                    for block in &resp.content {
+                        if let claude::ContentBlock::Text { text } = block {
+                            return Ok(text.clone());
+                        }
                     }
                 }
+                Err(e) => tracing::warn!("Claude failed: {}, trying Ollama", e),
             }
-            Err(e) => tracing::warn!("Claude failed: {}, trying Ollama", e),
+        } else {
+            tracing::info!("Claude unreachable, going straight to Ollama");
         }
     }
 
     // Fallback to Ollama
     if ollama::is_ollama_running().await {
-        return ollama::generate(prompt).await;
+        return ollama::generate_with_history(&messages).await;
     }
 
     Err(AiError::NoBackend)
@@ -1,10 +1,30 @@
-use tracing;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{oneshot, Mutex};
 
 /// MCP (Model Context Protocol) client integration
 ///
-/// Uses rmcp (official Rust SDK) for connecting to MCP servers.
-/// MCP servers provide tools that extend Claude's capabilities
-/// within the desktop shell.
+/// Talks to MCP servers over a newline-delimited JSON-RPC 2.0 transport on
+/// the child process's stdin/stdout, as described by the MCP spec. Servers
+/// are spawned as long-lived child processes; each `McpSession` keeps the
+/// child alive and multiplexes `tools/call` requests over a single pipe,
+/// matching responses back to callers by request id.
+
+#[derive(Debug, thiserror::Error)]
+pub enum McpError {
+    #[error("failed to spawn MCP server: {0}")]
+    Spawn(#[from] std::io::Error),
+    #[error("MCP server closed its connection")]
+    Closed,
+    #[error("MCP server returned an error: {0}")]
+    Server(String),
+    #[error("malformed JSON-RPC message: {0}")]
+    Protocol(#[from] serde_json::Error),
+}
 
 /// MCP server configuration
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -21,41 +41,233 @@ pub struct McpTool {
     pub name: String,
     pub description: String,
     pub server: String,
+    pub input_schema: Value,
 }
 
-/// Connect to an MCP server via child process transport
-///
-/// Note: rmcp integration requires the rmcp crate which has complex
-/// build requirements. This provides the interface - enable full
-/// MCP support by adding rmcp to dependencies when needed.
-pub async fn connect_mcp_server(
-    command: &str,
-    args: &[&str],
-) -> Result<Vec<McpTool>, Box<dyn std::error::Error>> {
-    tracing::info!("Connecting to MCP server: {} {:?}", command, args);
-
-    // Verify the command exists
-    let output = tokio::process::Command::new(command)
-        .args(args)
-        .arg("--version")
-        .output()
-        .await;
-
-    match output {
-        Ok(out) => {
-            tracing::info!(
-                "MCP server responded: {}",
-                String::from_utf8_lossy(&out.stdout)
-            );
+impl McpTool {
+    /// Convert to the `Tool` shape Claude expects in a `tools` request
+    pub fn to_claude_tool(&self) -> crate::claude::Tool {
+        crate::claude::Tool {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            input_schema: self.input_schema.clone(),
         }
-        Err(e) => {
-            tracing::warn!("MCP server not available: {}", e);
-            return Err(Box::new(e));
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct JsonRpcRequest<'a> {
+    jsonrpc: &'a str,
+    id: u64,
+    method: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    params: Option<Value>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct JsonRpcNotification<'a> {
+    jsonrpc: &'a str,
+    method: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    params: Option<Value>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct JsonRpcResponse {
+    id: Option<u64>,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<JsonRpcErrorObject>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct JsonRpcErrorObject {
+    message: String,
+}
+
+type PendingMap = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, McpError>>>>>;
+
+/// A live connection to a spawned MCP server
+///
+/// Keeps the child process alive for the lifetime of the session and routes
+/// `tools/call` requests to their responses by JSON-RPC id. The child is
+/// killed when the session is dropped.
+pub struct McpSession {
+    name: String,
+    child: Mutex<Child>,
+    stdin: Mutex<ChildStdin>,
+    next_id: AtomicU64,
+    pending: PendingMap,
+}
+
+impl McpSession {
+    async fn send(&self, line: String) -> Result<(), McpError> {
+        let mut stdin = self.stdin.lock().await;
+        stdin.write_all(line.as_bytes()).await?;
+        stdin.write_all(b"\n").await?;
+        Ok(())
+    }
+
+    async fn request(&self, method: &str, params: Option<Value>) -> Result<Value, McpError> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id, tx);
+
+        let payload = serde_json::to_string(&JsonRpcRequest {
+            jsonrpc: "2.0",
+            id,
+            method,
+            params,
+        })?;
+        self.send(payload).await?;
+
+        rx.await.map_err(|_| McpError::Closed)?
+    }
+
+    async fn notify(&self, method: &str, params: Option<Value>) -> Result<(), McpError> {
+        let payload = serde_json::to_string(&JsonRpcNotification {
+            jsonrpc: "2.0",
+            method,
+            params,
+        })?;
+        self.send(payload).await
+    }
+
+    /// Invoke `tools/call` on the server and return the raw JSON result
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value, McpError> {
+        self.request(
+            "tools/call",
+            Some(serde_json::json!({ "name": name, "arguments": arguments })),
+        )
+        .await
+    }
+
+    pub fn server_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Drop for McpSession {
+    fn drop(&mut self) {
+        if let Ok(mut child) = self.child.try_lock() {
+            let _ = child.start_kill();
         }
     }
+}
+
+/// Spawn an MCP server and perform the `initialize`/`initialized` handshake
+///
+/// Returns a live session (keep it around for the process lifetime so
+/// `tools/call` can be routed to it) plus the tools the server advertised
+/// via `tools/list`.
+pub async fn connect_mcp_server(
+    config: &McpServerConfig,
+) -> Result<(Arc<McpSession>, Vec<McpTool>), McpError> {
+    tracing::info!(
+        "Connecting to MCP server '{}': {} {:?}",
+        config.name,
+        config.command,
+        config.args
+    );
+
+    let mut child = Command::new(&config.command)
+        .args(&config.args)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let stdin = child.stdin.take().expect("child spawned with piped stdin");
+    let stdout = child
+        .stdout
+        .take()
+        .expect("child spawned with piped stdout");
+
+    let pending: PendingMap = Arc::new(Mutex::new(HashMap::new()));
+    spawn_reader(config.name.clone(), stdout, pending.clone());
+
+    let session = Arc::new(McpSession {
+        name: config.name.clone(),
+        child: Mutex::new(child),
+        stdin: Mutex::new(stdin),
+        next_id: AtomicU64::new(1),
+        pending,
+    });
+
+    let init_params = serde_json::json!({
+        "protocolVersion": "2024-11-05",
+        "capabilities": {},
+        "clientInfo": { "name": "ai-ui", "version": "0.1.0" }
+    });
+    session.request("initialize", Some(init_params)).await?;
+    session.notify("initialized", None).await?;
+
+    let tools = session.request("tools/list", None).await?;
+    let tools = tools["tools"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|tool| {
+            Some(McpTool {
+                name: tool["name"].as_str()?.to_string(),
+                description: tool["description"].as_str().unwrap_or_default().to_string(),
+                server: config.name.clone(),
+                input_schema: tool["inputSchema"].clone(),
+            })
+        })
+        .collect();
+
+    Ok((session, tools))
+}
 
-    // Placeholder for full rmcp integration
-    Ok(Vec::new())
+/// Background task: read newline-delimited JSON-RPC messages from the
+/// server's stdout, buffering partial lines, and dispatch responses to the
+/// caller awaiting that request id.
+fn spawn_reader(server_name: String, stdout: tokio::process::ChildStdout, pending: PendingMap) {
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<JsonRpcResponse>(&line) {
+                        Ok(resp) => {
+                            let Some(id) = resp.id else { continue };
+                            if let Some(tx) = pending.lock().await.remove(&id) {
+                                let result = match resp.error {
+                                    Some(err) => Err(McpError::Server(err.message)),
+                                    None => Ok(resp.result.unwrap_or(Value::Null)),
+                                };
+                                let _ = tx.send(result);
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!(
+                                "MCP server '{}' sent a malformed message: {}",
+                                server_name,
+                                e
+                            );
+                        }
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::warn!("MCP server '{}' stdout error: {}", server_name, e);
+                    break;
+                }
+            }
+        }
+
+        // The server is gone — unblock anything still waiting on a response.
+        for (_, tx) in pending.lock().await.drain() {
+            let _ = tx.send(Err(McpError::Closed));
+        }
+    });
 }
 
 /// Load MCP server configurations from config file
@@ -85,3 +297,54 @@ pub fn load_mcp_configs() -> Vec<McpServerConfig> {
         }
     }
 }
+
+/// Every MCP server connected for one agent run, plus the routing needed to
+/// dispatch a `tool_use` block to the server that advertised it.
+pub struct McpToolset {
+    /// Kept alive for the run's duration — dropping a session kills its
+    /// child process.
+    pub sessions: Vec<Arc<McpSession>>,
+    /// Tool schemas to advertise to Claude, `desktop_tools()` first.
+    pub tools: Vec<crate::claude::Tool>,
+    tool_servers: HashMap<String, Arc<McpSession>>,
+}
+
+impl McpToolset {
+    /// Run an MCP `tools/call`, routed by tool name to the server that
+    /// advertised it. Returns `None` for tool names this toolset doesn't
+    /// own (e.g. the built-in desktop tools), which the caller should
+    /// handle itself.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Option<Result<Value, McpError>> {
+        let session = self.tool_servers.get(name)?;
+        Some(session.call_tool(name, arguments).await)
+    }
+}
+
+/// Connect to every enabled server from `load_mcp_configs()` and aggregate
+/// their tools alongside Claude's built-in `desktop_tools()`
+pub async fn connect_enabled_servers() -> McpToolset {
+    let mut sessions = Vec::new();
+    let mut tools: Vec<crate::claude::Tool> = crate::claude::desktop_tools();
+    let mut tool_servers = HashMap::new();
+
+    for config in load_mcp_configs().into_iter().filter(|c| c.enabled) {
+        match connect_mcp_server(&config).await {
+            Ok((session, mcp_tools)) => {
+                for tool in &mcp_tools {
+                    tool_servers.insert(tool.name.clone(), session.clone());
+                }
+                tools.extend(mcp_tools.iter().map(McpTool::to_claude_tool));
+                sessions.push(session);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to connect to MCP server '{}': {}", config.name, e);
+            }
+        }
+    }
+
+    McpToolset {
+        sessions,
+        tools,
+        tool_servers,
+    }
+}
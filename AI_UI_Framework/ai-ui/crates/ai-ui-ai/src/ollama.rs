@@ -27,6 +27,27 @@ pub async fn generate(prompt: &str) -> Result<String, AiError> {
     Ok(res.response)
 }
 
+/// Generate a response from a full conversation history
+///
+/// Ollama's completion API takes a single prompt rather than a message
+/// array, so the history is flattened into a transcript before generating.
+pub async fn generate_with_history(messages: &[crate::claude::Message]) -> Result<String, AiError> {
+    let prompt = messages
+        .iter()
+        .map(|m| {
+            let text = m
+                .content
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| m.content.to_string());
+            format!("{}: {}", m.role, text)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    generate(&prompt).await
+}
+
 /// List available Ollama models
 pub async fn list_models() -> Result<Vec<String>, AiError> {
     let ollama = Ollama::default();
@@ -0,0 +1,147 @@
+use crate::claude::Message;
+
+/// Reserved headroom for the model's own reply, so trimming leaves room for
+/// `max_tokens` in the outgoing request.
+const RESPONSE_HEADROOM: usize = 4096;
+
+/// Estimate the token count of `text` for `model`.
+///
+/// Claude and GPT-family models are close enough to `cl100k_base` for
+/// budgeting purposes, so they go through a real `tiktoken-rs` BPE encoder;
+/// everything else — local Ollama models — falls back to a chars-per-token
+/// heuristic.
+pub fn count_tokens(model: &str, text: &str) -> usize {
+    if is_bpe_model(model) {
+        if let Ok(bpe) = tiktoken_rs::cl100k_base() {
+            return bpe.encode_with_special_tokens(text).len();
+        }
+    }
+
+    text.chars().count().div_ceil(4)
+}
+
+fn is_bpe_model(model: &str) -> bool {
+    model.starts_with("claude") || model.starts_with("gpt")
+}
+
+/// The context window, in tokens, for a known model family. Unknown models
+/// get a conservative default sized for small local Ollama models.
+pub fn context_window(model: &str) -> usize {
+    if model.starts_with("claude") {
+        200_000
+    } else if model.starts_with("gpt-4") {
+        128_000
+    } else {
+        8_192
+    }
+}
+
+/// Token usage for a prompt plus history against a model's context window.
+pub struct TokenBudget {
+    pub used: usize,
+    pub window: usize,
+}
+
+impl TokenBudget {
+    pub fn overflowing(&self) -> bool {
+        self.used > self.window
+    }
+}
+
+/// Count tokens used by `history` plus `prompt` against `model`'s window.
+pub fn budget_for(model: &str, history: &[Message], prompt: &str) -> TokenBudget {
+    let used = history
+        .iter()
+        .map(|m| count_tokens(model, message_text(m)))
+        .sum::<usize>()
+        + count_tokens(model, prompt);
+
+    TokenBudget {
+        used,
+        window: context_window(model),
+    }
+}
+
+/// Drop the oldest turns from `history` until it, plus `prompt`, fits inside
+/// `model`'s context window, leaving room for the response.
+pub fn trim_to_fit(model: &str, history: &mut Vec<Message>, prompt: &str) {
+    let budget = context_window(model).saturating_sub(RESPONSE_HEADROOM);
+    let prompt_tokens = count_tokens(model, prompt);
+
+    while !history.is_empty() {
+        let used: usize = history
+            .iter()
+            .map(|m| count_tokens(model, message_text(m)))
+            .sum::<usize>()
+            + prompt_tokens;
+        if used <= budget {
+            break;
+        }
+        history.remove(0);
+    }
+}
+
+fn message_text(message: &Message) -> &str {
+    message.content.as_str().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(text: &str) -> Message {
+        Message {
+            role: "user".to_string(),
+            content: serde_json::Value::String(text.to_string()),
+        }
+    }
+
+    #[test]
+    fn context_window_matches_known_model_families() {
+        assert_eq!(context_window("claude-sonnet-4-5-20250929"), 200_000);
+        assert_eq!(context_window("gpt-4o"), 128_000);
+        assert_eq!(context_window("llama3.2"), 8_192);
+    }
+
+    #[test]
+    fn budget_for_sums_history_and_prompt_tokens() {
+        let history = vec![message("hello"), message("world")];
+        let budget = budget_for("llama3.2", &history, "one more");
+        let expected = count_tokens("llama3.2", "hello")
+            + count_tokens("llama3.2", "world")
+            + count_tokens("llama3.2", "one more");
+        assert_eq!(budget.used, expected);
+        assert_eq!(budget.window, 8_192);
+    }
+
+    #[test]
+    fn token_budget_overflowing_compares_used_to_window() {
+        assert!(!TokenBudget { used: 10, window: 10 }.overflowing());
+        assert!(TokenBudget { used: 11, window: 10 }.overflowing());
+    }
+
+    #[test]
+    fn trim_to_fit_drops_oldest_turns_first_until_it_fits() {
+        // Each turn is ~2500 tokens (chars/4) against an 8_192-token window
+        // less 4_096 of response headroom, so only the most recent turn
+        // can survive.
+        let mut history: Vec<Message> = (0..10)
+            .map(|i| message(&format!("turn-{}-{}", i, "x".repeat(10_000))))
+            .collect();
+
+        trim_to_fit("llama3.2", &mut history, "prompt");
+
+        assert!(history.len() < 10);
+        assert!(history
+            .first()
+            .map(|m| message_text(m).starts_with(&format!("turn-{}", 10 - history.len())))
+            .unwrap_or(true));
+    }
+
+    #[test]
+    fn trim_to_fit_leaves_empty_history_untouched() {
+        let mut history: Vec<Message> = Vec::new();
+        trim_to_fit("claude-sonnet-4-5-20250929", &mut history, "short prompt");
+        assert!(history.is_empty());
+    }
+}
@@ -1,13 +1,18 @@
 use crate::command_bar;
 use crate::launcher;
+use crate::platform;
 use crate::taskbar;
 
 use ai_ui_system::apps::AppEntry;
 use ai_ui_system::status::SystemStatus;
 
 use iced::widget::{column, container, text};
+use iced::window;
 use iced::{Element, Length, Subscription, Task, Theme};
 
+/// Height, in logical pixels, of the taskbar panel window.
+const TASKBAR_HEIGHT: u32 = 40;
+
 /// Main application state
 pub struct AiUiShell {
     pub command_input: String,
@@ -21,6 +26,30 @@ pub struct AiUiShell {
     pub is_launcher_visible: bool,
     pub launcher_query: String,
     pub api_key: Option<String>,
+
+    // Window identities — the taskbar and the command-bar/launcher each
+    // get their own OS window so they can be configured as a real panel
+    // / overlay (see `platform::linux`) instead of being rows inside the
+    // desktop window.
+    pub desktop_window: window::Id,
+    pub taskbar_window: window::Id,
+    pub overlay_window: Option<window::Id>,
+
+    // Conversation history
+    pub conversation_store: Option<ai_ui_ai::history::ConversationStore>,
+    pub active_conversation: Option<ai_ui_ai::history::ConversationId>,
+    pub conversation_messages: Vec<ai_ui_ai::history::StoredMessage>,
+    pub saved_conversations: Vec<ai_ui_ai::history::ConversationSummary>,
+    pending_prompt: String,
+
+    // Slash commands
+    pub command_outputs: Vec<command_bar::CommandOutput>,
+
+    // Agentic tool use
+    pub current_tool: Option<String>,
+
+    // Tools external processes registered over the local IPC server
+    pub ipc_registry: std::sync::Arc<ai_ui_system::ipc::ToolRegistry>,
 }
 
 #[derive(Debug, Clone)]
@@ -29,13 +58,17 @@ pub enum Message {
     CommandInputChanged(String),
     ExecuteCommand,
     AiResponseChunk(String),
-    AiResponseComplete(String),
+    AiResponseComplete,
     AiError(String),
 
     // App Launcher
     ToggleCommandBar,
     ToggleLauncher,
     LauncherQueryChanged(String),
+    /// App display name (not the raw `exec` string) to launch via
+    /// `apps::launch_by_name`, which knows how to dispatch Flatpak/Snap/
+    /// AppImage entries, wrap `Terminal=true` apps and normalize the
+    /// child environment.
     LaunchApp(String),
     AppsLoaded(Vec<AppEntry>),
 
@@ -45,12 +78,62 @@ pub enum Message {
 
     // Taskbar
     TaskbarAction(taskbar::TaskbarAction),
+
+    // Conversation history
+    StartNewConversation,
+    SwitchConversation(ai_ui_ai::history::ConversationId),
+
+    // Slash commands
+    RunSlashCommand(command_bar::SlashCommand),
+    ToggleOutputFold(usize),
+
+    // Agentic tool use
+    ToolStarted(String),
+    ToolFinished,
+
+    // Connectivity
+    ConnectivityChanged { claude_reachable: bool, ollama_reachable: bool },
+
+    // Platform window wiring
+    PanelConfigured(Result<(), String>),
 }
 
 impl AiUiShell {
     pub fn new() -> (Self, Task<Message>) {
         let api_key = ai_ui_ai::load_api_key();
 
+        let conversation_store = ai_ui_ai::history::ConversationStore::open()
+            .map_err(|e| tracing::warn!("Failed to open conversation store: {}", e))
+            .ok();
+        let saved_conversations = conversation_store
+            .as_ref()
+            .and_then(|store| store.list_conversations().ok())
+            .unwrap_or_default();
+        let active_conversation = saved_conversations.first().map(|c| c.id).or_else(|| {
+            conversation_store
+                .as_ref()
+                .and_then(|store| store.new_conversation("New Conversation").ok())
+        });
+        let conversation_messages = match (&conversation_store, active_conversation) {
+            (Some(store), Some(id)) => store.load_messages(id).unwrap_or_default(),
+            _ => Vec::new(),
+        };
+
+        let (desktop_window, open_desktop) = window::open(window::Settings {
+            size: iced::Size::new(1920.0, 1080.0),
+            decorations: false,
+            transparent: true,
+            ..Default::default()
+        });
+        let (taskbar_window, open_taskbar) = window::open(window::Settings {
+            size: iced::Size::new(1920.0, TASKBAR_HEIGHT as f32),
+            position: window::Position::Specific(iced::Point::new(0.0, 1080.0 - TASKBAR_HEIGHT as f32)),
+            decorations: false,
+            transparent: true,
+            resizable: false,
+            ..Default::default()
+        });
+
         let app = Self {
             command_input: String::new(),
             ai_response: String::new(),
@@ -63,6 +146,17 @@ impl AiUiShell {
             is_launcher_visible: false,
             launcher_query: String::new(),
             api_key,
+            desktop_window,
+            taskbar_window,
+            overlay_window: None,
+            conversation_store,
+            active_conversation,
+            conversation_messages,
+            saved_conversations,
+            pending_prompt: String::new(),
+            command_outputs: Vec::new(),
+            current_tool: None,
+            ipc_registry: std::sync::Arc::new(ai_ui_system::ipc::ToolRegistry::default()),
         };
 
         // Load installed apps on startup
@@ -70,7 +164,25 @@ impl AiUiShell {
             Message::AppsLoaded(result.unwrap_or_default())
         });
 
-        (app, init_cmd)
+        // Accept external tool registrations / status queries over the
+        // local IPC socket for the lifetime of the process.
+        let ipc_registry = app.ipc_registry.clone();
+        let serve_ipc = Task::future(async move {
+            if let Err(e) = ai_ui_system::ipc::serve(ipc_registry).await {
+                tracing::warn!("IPC server exited: {}", e);
+            }
+        })
+        .discard();
+
+        // The taskbar reserves real screen space as a wlr-layer-shell /
+        // X11 dock panel once its window actually exists.
+        let configure_taskbar =
+            open_taskbar.then(|id| platform::configure_panel(id, TASKBAR_HEIGHT));
+
+        (
+            app,
+            Task::batch([init_cmd, serve_ipc, open_desktop.discard(), configure_taskbar]),
+        )
     }
 
     pub fn theme(&self) -> Theme {
@@ -89,27 +201,150 @@ impl AiUiShell {
                     return Task::none();
                 }
 
+                // A line that already contains a `/` command re-runs it on
+                // Enter instead of going through the AI backend.
+                if let Some(cmd) = command_bar::parse_slash_command(&prompt) {
+                    return self.update(Message::RunSlashCommand(cmd));
+                }
+
                 self.ai_response.clear();
                 self.ai_streaming = true;
+                self.pending_prompt = prompt.clone();
 
                 let api_key = self.api_key.clone();
-                Task::perform(
-                    async move {
-                        ai_ui_ai::generate_response(&prompt, api_key.as_deref()).await
-                    },
-                    |result| match result {
-                        Ok(response) => Message::AiResponseComplete(response),
-                        Err(e) => Message::AiError(e.to_string()),
-                    },
+                let claude_reachable = self.system_status.claude_reachable;
+                let ipc_registry = self.ipc_registry.clone();
+                let model = if api_key.is_some() {
+                    "claude-sonnet-4-5-20250929"
+                } else {
+                    "llama3.2"
+                };
+                let mut history: Vec<ai_ui_ai::claude::Message> = self
+                    .conversation_messages
+                    .iter()
+                    .map(|m| m.to_claude_message())
+                    .collect();
+                ai_ui_ai::tokens::trim_to_fit(model, &mut history, &prompt);
+                Task::run(
+                    iced::stream::channel(100, move |mut output| async move {
+                        // No Claude key configured, or a connectivity probe
+                        // already found it unreachable — go straight to the
+                        // non-streaming Ollama path in one shot.
+                        let key = if claude_reachable { api_key } else { None };
+                        let Some(key) = key else {
+                            match ai_ui_ai::generate_response(
+                                &prompt,
+                                None,
+                                &history,
+                                claude_reachable,
+                            )
+                            .await
+                            {
+                                Ok(text) => {
+                                    let _ = output.send(Message::AiResponseChunk(text)).await;
+                                    let _ = output.send(Message::AiResponseComplete).await;
+                                }
+                                Err(e) => {
+                                    let _ = output.send(Message::AiError(e.to_string())).await;
+                                }
+                            }
+                            return;
+                        };
+
+                        let client = ai_ui_ai::claude::ClaudeClient::new(key);
+                        let mut messages = history;
+                        messages.push(ai_ui_ai::claude::Message {
+                            role: "user".into(),
+                            content: serde_json::Value::String(prompt),
+                        });
+
+                        let toolset = std::sync::Arc::new(ai_ui_ai::mcp::connect_enabled_servers().await);
+                        let mut tools = toolset.tools.clone();
+                        tools.extend(ipc_registry.list().await.into_iter().map(
+                            |(name, description, input_schema)| ai_ui_ai::claude::Tool {
+                                name,
+                                description,
+                                input_schema,
+                            },
+                        ));
+                        let tool_output = output.clone();
+                        let mut text_output = output.clone();
+
+                        let result = client
+                            .run_agent(
+                                messages,
+                                tools,
+                                move |delta| {
+                                    let _ = text_output.try_send(Message::AiResponseChunk(delta));
+                                },
+                                move |name, input| {
+                                    let name = name.to_string();
+                                    let input = input.clone();
+                                    let mut tool_output = tool_output.clone();
+                                    let toolset = toolset.clone();
+                                    let ipc_registry = ipc_registry.clone();
+                                    Box::pin(async move {
+                                        let _ = tool_output
+                                            .try_send(Message::ToolStarted(name.clone()));
+
+                                        let result =
+                                            run_desktop_tool(&name, &input, &toolset, &ipc_registry)
+                                                .await;
+                                        let _ = tool_output.try_send(Message::ToolFinished);
+                                        result
+                                    })
+                                },
+                            )
+                            .await;
+
+                        match result {
+                            Ok(_) => {
+                                let _ = output.send(Message::AiResponseComplete).await;
+                            }
+                            Err(e) => {
+                                let _ = output.send(Message::AiError(e.to_string())).await;
+                            }
+                        }
+                    }),
+                    std::convert::identity,
                 )
             }
             Message::AiResponseChunk(chunk) => {
                 self.ai_response.push_str(&chunk);
                 Task::none()
             }
-            Message::AiResponseComplete(response) => {
-                self.ai_response = response;
+            Message::AiResponseComplete => {
                 self.ai_streaming = false;
+
+                if let (Some(store), Some(conv_id)) =
+                    (&self.conversation_store, self.active_conversation)
+                {
+                    if let Ok(id) = store.append_message(conv_id, "user", &self.pending_prompt, None)
+                    {
+                        self.conversation_messages.push(ai_ui_ai::history::StoredMessage {
+                            id,
+                            conversation_id: conv_id,
+                            role: "user".into(),
+                            content: self.pending_prompt.clone(),
+                            model: None,
+                            created_at: 0,
+                        });
+                    }
+                    if let Ok(id) =
+                        store.append_message(conv_id, "assistant", &self.ai_response, None)
+                    {
+                        self.conversation_messages.push(ai_ui_ai::history::StoredMessage {
+                            id,
+                            conversation_id: conv_id,
+                            role: "assistant".into(),
+                            content: self.ai_response.clone(),
+                            model: None,
+                            created_at: 0,
+                        });
+                    }
+                }
+                self.pending_prompt.clear();
+
                 Task::none()
             }
             Message::AiError(err) => {
@@ -119,19 +354,23 @@ impl AiUiShell {
             }
             Message::ToggleCommandBar => {
                 self.is_command_bar_visible = !self.is_command_bar_visible;
-                if !self.is_command_bar_visible {
-                    self.command_input.clear();
-                    self.ai_response.clear();
+                if self.is_command_bar_visible {
+                    self.is_launcher_visible = false;
+                    return self.open_overlay_window();
                 }
-                Task::none()
+                self.command_input.clear();
+                self.ai_response.clear();
+                self.close_overlay_window()
             }
             Message::ToggleLauncher => {
                 self.is_launcher_visible = !self.is_launcher_visible;
-                if !self.is_launcher_visible {
-                    self.launcher_query.clear();
-                    self.search_results.clear();
+                if self.is_launcher_visible {
+                    self.is_command_bar_visible = false;
+                    return self.open_overlay_window();
                 }
-                Task::none()
+                self.launcher_query.clear();
+                self.search_results.clear();
+                self.close_overlay_window()
             }
             Message::LauncherQueryChanged(query) => {
                 self.search_results =
@@ -139,20 +378,14 @@ impl AiUiShell {
                 self.launcher_query = query;
                 Task::none()
             }
-            Message::LaunchApp(exec_path) => {
-                #[cfg(windows)]
-                {
-                    let _ = std::process::Command::new("cmd")
-                        .args(["/C", "start", "", &exec_path])
-                        .spawn();
-                }
-                #[cfg(not(windows))]
-                {
-                    let exec = exec_path.split_whitespace().next().unwrap_or(&exec_path);
-                    let _ = std::process::Command::new(exec).spawn();
-                }
+            Message::LaunchApp(app_name) => {
                 self.is_launcher_visible = false;
-                Task::none()
+                Task::future(async move {
+                    if let Err(e) = ai_ui_system::apps::launch_by_name(&app_name, None).await {
+                        tracing::warn!("Failed to launch {}: {}", app_name, e);
+                    }
+                })
+                .discard()
             }
             Message::AppsLoaded(apps) => {
                 tracing::info!("Loaded {} installed apps", apps.len());
@@ -160,7 +393,19 @@ impl AiUiShell {
                 Task::none()
             }
             Message::SystemStatusUpdate(status) => {
+                let connectivity_changed = status.claude_reachable
+                    != self.system_status.claude_reachable
+                    || status.ollama_reachable != self.system_status.ollama_reachable;
+                let claude_reachable = status.claude_reachable;
+                let ollama_reachable = status.ollama_reachable;
                 self.system_status = status;
+
+                if connectivity_changed {
+                    return self.update(Message::ConnectivityChanged {
+                        claude_reachable,
+                        ollama_reachable,
+                    });
+                }
                 Task::none()
             }
             Message::Tick => {
@@ -172,49 +417,286 @@ impl AiUiShell {
                 taskbar::handle_action(&mut self.taskbar_state, action);
                 Task::none()
             }
+            Message::StartNewConversation => {
+                if let Some(store) = &self.conversation_store {
+                    if let Ok(id) = store.new_conversation("New Conversation") {
+                        self.active_conversation = Some(id);
+                        self.conversation_messages.clear();
+                        self.saved_conversations = store.list_conversations().unwrap_or_default();
+                    }
+                }
+                self.ai_response.clear();
+                Task::none()
+            }
+            Message::SwitchConversation(id) => {
+                if let Some(store) = &self.conversation_store {
+                    self.conversation_messages = store.load_messages(id).unwrap_or_default();
+                    self.active_conversation = Some(id);
+                }
+                self.ai_response.clear();
+                Task::none()
+            }
+            Message::RunSlashCommand(cmd) => {
+                let (label, output) = match cmd {
+                    command_bar::SlashCommand::File(path) => {
+                        let output = std::fs::read_to_string(&path)
+                            .unwrap_or_else(|e| format!("Error reading {}: {}", path, e));
+                        (format!("/file {}", path), output)
+                    }
+                    command_bar::SlashCommand::Shell(cmd_str) => {
+                        let output = run_shell_command(&cmd_str);
+                        (format!("/shell {}", cmd_str), output)
+                    }
+                    command_bar::SlashCommand::Apps => {
+                        let output = self
+                            .installed_apps
+                            .iter()
+                            .map(|a| a.name.clone())
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        ("/apps".to_string(), output)
+                    }
+                    command_bar::SlashCommand::Model(name) => {
+                        let output = if name.is_empty() {
+                            "Usage: /model <name>".to_string()
+                        } else {
+                            format!("Model switching isn't implemented yet (requested: {})", name)
+                        };
+                        (format!("/model {}", name), output)
+                    }
+                    command_bar::SlashCommand::Clear => {
+                        self.ai_response.clear();
+                        self.command_outputs.clear();
+                        return Task::none();
+                    }
+                };
+
+                self.command_outputs.push(command_bar::CommandOutput {
+                    command: label,
+                    output,
+                    folded: true,
+                });
+                Task::none()
+            }
+            Message::ToggleOutputFold(index) => {
+                if let Some(entry) = self.command_outputs.get_mut(index) {
+                    entry.folded = !entry.folded;
+                }
+                Task::none()
+            }
+            Message::ToolStarted(name) => {
+                self.current_tool = Some(name);
+                Task::none()
+            }
+            Message::ToolFinished => {
+                self.current_tool = None;
+                Task::none()
+            }
+            Message::ConnectivityChanged {
+                claude_reachable,
+                ollama_reachable,
+            } => {
+                let note = match (claude_reachable, ollama_reachable) {
+                    (false, false) => "Offline — no AI backend reachable".to_string(),
+                    (false, true) => {
+                        "Claude unreachable — switched to the local Ollama model".to_string()
+                    }
+                    (true, _) => "Back online — Claude is reachable again".to_string(),
+                };
+                self.command_outputs.push(command_bar::CommandOutput {
+                    command: "connectivity".to_string(),
+                    output: note,
+                    folded: false,
+                });
+                Task::none()
+            }
+            Message::PanelConfigured(Err(err)) => {
+                tracing::warn!("Failed to configure shell window: {}", err);
+                Task::none()
+            }
+            Message::PanelConfigured(Ok(())) => Task::none(),
+        }
+    }
+
+    /// Open the shared command-bar/launcher overlay window, if it isn't
+    /// already open.
+    fn open_overlay_window(&mut self) -> Task<Message> {
+        if self.overlay_window.is_some() {
+            return Task::none();
+        }
+
+        let (id, open) = window::open(window::Settings {
+            size: iced::Size::new(900.0, 700.0),
+            decorations: false,
+            transparent: true,
+            ..Default::default()
+        });
+        self.overlay_window = Some(id);
+
+        open.then(platform::configure_overlay)
+    }
+
+    /// Close the overlay window, if one is open.
+    fn close_overlay_window(&mut self) -> Task<Message> {
+        match self.overlay_window.take() {
+            Some(id) => window::close(id).discard(),
+            None => Task::none(),
+        }
+    }
+
+    pub fn title(&self, id: window::Id) -> String {
+        if id == self.taskbar_window {
+            "AI-UI Taskbar".to_string()
+        } else if Some(id) == self.overlay_window {
+            "AI-UI Command Bar".to_string()
+        } else {
+            "AI-UI Shell".to_string()
+        }
+    }
+
+    pub fn view(&self, id: window::Id) -> Element<'_, Message> {
+        if id == self.taskbar_window {
+            return taskbar::view(&self.taskbar_state, &self.system_status);
         }
+
+        if Some(id) == self.overlay_window {
+            return self.overlay_view();
+        }
+
+        self.desktop_view()
     }
 
-    pub fn view(&self) -> Element<'_, Message> {
-        let taskbar_view = taskbar::view(&self.taskbar_state, &self.system_status);
+    fn overlay_view(&self) -> Element<'_, Message> {
+        if self.is_command_bar_visible {
+            let model = if self.api_key.is_some() {
+                "claude-sonnet-4-5-20250929"
+            } else {
+                "llama3.2"
+            };
+            let history: Vec<ai_ui_ai::claude::Message> = self
+                .conversation_messages
+                .iter()
+                .map(|m| m.to_claude_message())
+                .collect();
+            let budget = ai_ui_ai::tokens::budget_for(model, &history, &self.command_input);
 
-        let main_content: Element<Message> = if self.is_command_bar_visible {
-            command_bar::view(&self.command_input, &self.ai_response, self.ai_streaming)
-        } else if self.is_launcher_visible {
+            command_bar::view(
+                &self.command_input,
+                &self.ai_response,
+                self.ai_streaming,
+                &self.command_outputs,
+                budget.used,
+                budget.window,
+                self.current_tool.as_deref(),
+            )
+        } else {
             launcher::view(
                 &self.launcher_query,
                 &self.search_results,
                 &self.installed_apps,
             )
-        } else {
-            // Desktop area
-            container(
-                column![
-                    text("AI-UI Desktop Shell").size(32),
-                    text("Press Ctrl+Space for AI Command Bar").size(16),
-                    text("Press Ctrl+Shift+A for App Launcher").size(16),
-                ]
-                .spacing(10)
-                .align_x(iced::Alignment::Center),
-            )
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .center_x(Length::Fill)
-            .center_y(Length::Fill)
-            .into()
-        };
-
-        let desktop = container(main_content)
-            .width(Length::Fill)
-            .height(Length::Fill);
+        }
+    }
 
-        column![desktop, taskbar_view]
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .into()
+    fn desktop_view(&self) -> Element<'_, Message> {
+        container(
+            column![
+                text("AI-UI Desktop Shell").size(32),
+                text("Press Ctrl+Space for AI Command Bar").size(16),
+                text("Press Ctrl+Shift+A for App Launcher").size(16),
+            ]
+            .spacing(10)
+            .align_x(iced::Alignment::Center),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x(Length::Fill)
+        .center_y(Length::Fill)
+        .into()
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
         iced::time::every(std::time::Duration::from_secs(1)).map(|_| Message::Tick)
     }
 }
+
+/// Dispatch a `tool_use` block from the agent loop: built-in desktop tools
+/// are handled directly against `ai-ui-system`, MCP-advertised tools are
+/// routed to whichever server advertised them, and anything else is tried
+/// against tools external processes registered over the local IPC socket.
+async fn run_desktop_tool(
+    name: &str,
+    input: &serde_json::Value,
+    toolset: &ai_ui_ai::mcp::McpToolset,
+    ipc_registry: &ai_ui_system::ipc::ToolRegistry,
+) -> String {
+    match name {
+        "launch_app" => {
+            let app_name = input["app_name"].as_str().unwrap_or_default();
+            let action_name = input["action_name"].as_str();
+            match ai_ui_system::apps::launch_by_name(app_name, action_name).await {
+                Ok(()) => format!("Launched {}", app_name),
+                Err(e) => format!("Failed to launch {}: {}", app_name, e),
+            }
+        }
+        "system_command" => {
+            let action = input["action"].as_str().unwrap_or_default();
+            ai_ui_system::status::execute_action(action).await
+        }
+        "open_with" => {
+            let path_str = input["path"].as_str().unwrap_or_default();
+            let app_name = input["app_name"].as_str();
+            let path = std::path::Path::new(path_str);
+
+            let candidates = match ai_ui_system::apps::apps_for_path(path).await {
+                Ok(candidates) => candidates,
+                Err(e) => return format!("Could not find handlers for {}: {}", path_str, e),
+            };
+
+            let app = match app_name {
+                Some(name) => ai_ui_system::apps::fuzzy_search(&candidates, name)
+                    .into_iter()
+                    .next(),
+                None => candidates.into_iter().next(),
+            };
+
+            let Some(app) = app else {
+                return format!("No application found to open {}", path_str);
+            };
+
+            match ai_ui_system::apps::open_with(path, &app).await {
+                Ok(()) => format!("Opened {} with {}", path_str, app.name),
+                Err(e) => format!("Failed to open {}: {}", path_str, e),
+            }
+        }
+        _ => match toolset.call_tool(name, input.clone()).await {
+            Some(Ok(value)) => value.to_string(),
+            Some(Err(e)) => format!("tool '{}' failed: {}", name, e),
+            None => match ipc_registry.call(name, input.clone()).await {
+                Ok(result) => result,
+                Err(e) => format!("unknown tool: {} ({})", name, e),
+            },
+        },
+    }
+}
+
+/// Run a `/shell` command and capture its combined stdout/stderr
+fn run_shell_command(cmd: &str) -> String {
+    if cmd.is_empty() {
+        return "Usage: /shell <command>".to_string();
+    }
+
+    #[cfg(windows)]
+    let output = std::process::Command::new("cmd").args(["/C", cmd]).output();
+    #[cfg(not(windows))]
+    let output = std::process::Command::new("sh").args(["-c", cmd]).output();
+
+    match output {
+        Ok(out) => {
+            let mut combined = String::from_utf8_lossy(&out.stdout).to_string();
+            combined.push_str(&String::from_utf8_lossy(&out.stderr));
+            combined
+        }
+        Err(e) => format!("Failed to run command: {}", e),
+    }
+}
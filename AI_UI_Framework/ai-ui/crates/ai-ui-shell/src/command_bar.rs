@@ -1,18 +1,118 @@
 use crate::app::Message;
-use iced::widget::{column, container, scrollable, text, text_input};
+use iced::widget::{button, column, container, row, scrollable, text, text_input};
 use iced::{Element, Length};
 
+/// A built-in slash command, parsed from the command bar input.
+#[derive(Debug, Clone)]
+pub enum SlashCommand {
+    File(String),
+    Shell(String),
+    Apps,
+    Model(String),
+    Clear,
+}
+
+/// One entry in the completion list shown while typing a leading `/`.
+struct SlashCommandSpec {
+    name: &'static str,
+    usage: &'static str,
+    description: &'static str,
+}
+
+const SLASH_COMMANDS: &[SlashCommandSpec] = &[
+    SlashCommandSpec {
+        name: "file",
+        usage: "/file <path>",
+        description: "Insert a file's contents",
+    },
+    SlashCommandSpec {
+        name: "shell",
+        usage: "/shell <cmd>",
+        description: "Run a shell command",
+    },
+    SlashCommandSpec {
+        name: "apps",
+        usage: "/apps",
+        description: "List installed apps",
+    },
+    SlashCommandSpec {
+        name: "model",
+        usage: "/model <name>",
+        description: "Switch the active model",
+    },
+    SlashCommandSpec {
+        name: "clear",
+        usage: "/clear",
+        description: "Clear the response area",
+    },
+];
+
+/// A completed slash-command run, rendered as a foldable placeholder block.
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub command: String,
+    pub output: String,
+    pub folded: bool,
+}
+
+/// Parse a line starting with `/` into a `SlashCommand`, if it matches a
+/// known built-in. Returns `None` for plain prompts and unknown commands.
+pub fn parse_slash_command(input: &str) -> Option<SlashCommand> {
+    let rest = input.trim().strip_prefix('/')?;
+    let (name, arg) = match rest.split_once(char::is_whitespace) {
+        Some((name, arg)) => (name, arg.trim().to_string()),
+        None => (rest, String::new()),
+    };
+
+    match name {
+        "file" => Some(SlashCommand::File(arg)),
+        "shell" => Some(SlashCommand::Shell(arg)),
+        "apps" => Some(SlashCommand::Apps),
+        "model" => Some(SlashCommand::Model(arg)),
+        "clear" => Some(SlashCommand::Clear),
+        _ => None,
+    }
+}
+
 /// Render the AI command bar overlay
 pub fn view<'a>(
     input: &str,
     response: &str,
     streaming: bool,
+    command_outputs: &'a [CommandOutput],
+    tokens_used: usize,
+    tokens_window: usize,
+    current_tool: Option<&str>,
 ) -> Element<'a, Message> {
-    let input_field = text_input("Ask AI anything... (launch apps, control system, ask questions)", input)
-        .on_input(Message::CommandInputChanged)
-        .on_submit(Message::ExecuteCommand)
-        .padding(12)
-        .size(18);
+    let input_field = text_input(
+        "Ask AI anything... (launch apps, control system, ask questions)",
+        input,
+    )
+    .on_input(Message::CommandInputChanged)
+    .on_submit(Message::ExecuteCommand)
+    .padding(12)
+    .size(18);
+
+    let token_label = if tokens_used > tokens_window {
+        format!(
+            "{} / {} tokens — over budget, oldest turns will be trimmed",
+            tokens_used, tokens_window
+        )
+    } else {
+        format!("{} / {} tokens", tokens_used, tokens_window)
+    };
+
+    let mut bar = column![input_field, text(token_label).size(12)]
+        .spacing(8)
+        .width(Length::Fixed(700.0));
+
+    if let Some(tool) = current_tool {
+        bar = bar.push(text(format!("Running tool: {}...", tool)).size(13));
+    }
+
+    if let Some(completions) = completion_list(input) {
+        bar = bar.push(completions);
+    }
 
     let response_area: Element<'a, Message> = if !response.is_empty() {
         let status = if streaming { " (streaming...)" } else { "" };
@@ -29,17 +129,17 @@ pub fn view<'a>(
         .height(Length::Fixed(300.0))
         .into()
     } else {
-        container(
-            text("Type a command and press Enter").size(14),
-        )
-        .padding(16)
-        .width(Length::Fill)
-        .into()
+        container(text("Type a command and press Enter").size(14))
+            .padding(16)
+            .width(Length::Fill)
+            .into()
     };
 
-    let bar = column![input_field, response_area]
-        .spacing(8)
-        .width(Length::Fixed(700.0));
+    bar = bar.push(response_area);
+
+    if !command_outputs.is_empty() {
+        bar = bar.push(output_blocks(command_outputs));
+    }
 
     container(bar)
         .width(Length::Fill)
@@ -48,3 +148,66 @@ pub fn view<'a>(
         .padding(100)
         .into()
 }
+
+/// While the input starts with `/`, show the matching built-ins; selecting
+/// one runs it immediately against whatever arguments are already typed,
+/// rather than inserting template text into the input field.
+fn completion_list<'a>(input: &str) -> Option<Element<'a, Message>> {
+    let rest = input.trim_start().strip_prefix('/')?;
+    let (typed_name, typed_args) = match rest.split_once(char::is_whitespace) {
+        Some((name, args)) => (name, args.trim().to_string()),
+        None => (rest, String::new()),
+    };
+
+    let mut matches = column![].spacing(2);
+    let mut any = false;
+    for spec in SLASH_COMMANDS {
+        if !spec.name.starts_with(typed_name) {
+            continue;
+        }
+        any = true;
+
+        let command = parse_slash_command(&format!("/{} {}", spec.name, typed_args))
+            .unwrap_or(SlashCommand::Clear);
+        let row = row![
+            text(spec.usage).size(13),
+            text(spec.description).size(12),
+        ]
+        .spacing(12);
+
+        matches = matches.push(
+            button(row)
+                .on_press(Message::RunSlashCommand(command))
+                .padding(6)
+                .width(Length::Fill),
+        );
+    }
+
+    any.then(|| container(matches).width(Length::Fill).into())
+}
+
+/// Render prior slash-command runs as collapsible placeholder blocks.
+fn output_blocks<'a>(outputs: &'a [CommandOutput]) -> Element<'a, Message> {
+    let mut list = column![].spacing(4);
+    for (index, entry) in outputs.iter().enumerate() {
+        let header = button(text(format!(
+            "{} {}",
+            if entry.folded { "▶" } else { "▼" },
+            entry.command
+        )))
+        .on_press(Message::ToggleOutputFold(index))
+        .padding(6)
+        .width(Length::Fill);
+
+        list = list.push(header);
+        if !entry.folded {
+            list = list.push(
+                container(text(entry.output.clone()).size(13))
+                    .padding(8)
+                    .width(Length::Fill),
+            );
+        }
+    }
+
+    container(list).width(Length::Fill).into()
+}
@@ -14,8 +14,13 @@ pub fn view<'a>(
         .padding(12)
         .size(18);
 
+    // Frecency ordering for the empty-query case lives inside
+    // `fuzzy_search` itself, so route through it here too instead of
+    // falling back to raw enumeration order.
+    let empty_query_results;
     let apps_to_show = if query.is_empty() {
-        all_apps
+        empty_query_results = ai_ui_system::apps::fuzzy_search(all_apps, "");
+        &empty_query_results[..]
     } else {
         search_results
     };
@@ -30,7 +35,7 @@ pub fn view<'a>(
             .iter()
             .take(20)
             .map(|app| {
-                let exec = app.exec.clone();
+                let name = app.name.clone();
                 let desc = app.description.as_deref().unwrap_or("").to_string();
 
                 let label = if desc.is_empty() {
@@ -48,7 +53,7 @@ pub fn view<'a>(
                         .padding(8)
                         .width(Length::Fill),
                 )
-                .on_press(Message::LaunchApp(exec))
+                .on_press(Message::LaunchApp(name))
                 .width(Length::Fill)
                 .into()
             })
@@ -10,18 +10,27 @@ fn main() -> iced::Result {
         .with_max_level(tracing::Level::DEBUG)
         .init();
 
+    // Launched by the SCM as `<exe> --service` (see
+    // `platform::windows::service::install_service`): block here and let
+    // the service control dispatcher drive the supervisor loop instead of
+    // starting the GUI.
+    #[cfg(windows)]
+    if std::env::args().any(|arg| arg == "--service") {
+        if let Err(e) = platform::windows::service::run_as_service() {
+            tracing::error!("Asgard service dispatcher failed: {}", e);
+        }
+        return Ok(());
+    }
+
     tracing::info!("Starting AI-UI Desktop Shell");
 
-    iced::application(app::AiUiShell::new, app::AiUiShell::update, app::AiUiShell::view)
-        .title("AI-UI Shell")
+    // Multi-window daemon: the desktop, the taskbar panel, and the
+    // command-bar/launcher overlay are each real OS windows so the
+    // taskbar can reserve screen space as a proper panel instead of
+    // being drawn as a row inside the desktop window.
+    iced::daemon(app::AiUiShell::title, app::AiUiShell::update, app::AiUiShell::view)
         .subscription(app::AiUiShell::subscription)
         .theme(app::AiUiShell::theme)
-        .window(iced::window::Settings {
-            size: iced::Size::new(1920.0, 1080.0),
-            decorations: false,
-            transparent: true,
-            ..Default::default()
-        })
         .antialiasing(true)
-        .run()
+        .run_with(app::AiUiShell::new)
 }
@@ -11,6 +11,29 @@ pub mod shell {
     /// 2. Full Wayland compositor with smithay (advanced)
     ///    - Handles Wayland protocol, DRM/GBM, libinput
     ///    - Reference: COSMIC's cosmic-comp
+    ///
+    /// X11 sessions get a `_NET_WM_WINDOW_TYPE_DOCK` fallback instead —
+    /// there's no layer-shell equivalent there, but the window manager
+    /// hint achieves the same "reserve space, don't get covered" effect.
+    use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
+    use wayland_client::protocol::{wl_compositor, wl_surface};
+    use wayland_client::{delegate_noop, Connection, Proxy};
+    use wayland_protocols_wlr::layer_shell::v1::client::{
+        zwlr_layer_shell_v1::{self, ZwlrLayerShellV1},
+        zwlr_layer_surface_v1::{self, Anchor, KeyboardInteractivity, ZwlrLayerSurfaceV1},
+    };
+
+    /// Which role a surface plays, matching `zwlr_layer_shell_v1`'s layer +
+    /// exclusive-zone semantics
+    #[derive(Debug, Clone, Copy)]
+    pub enum PanelRole {
+        /// The taskbar — anchored to the bottom edge, reserves an
+        /// exclusive zone so normal windows don't draw under it.
+        Panel { height: u32 },
+        /// Command bar / launcher — anchored but floating above content,
+        /// no exclusive zone.
+        Overlay,
+    }
 
     /// Check if running under Wayland
     pub fn is_wayland() -> bool {
@@ -26,4 +49,214 @@ pub mod shell {
     pub fn session_type() -> String {
         std::env::var("XDG_SESSION_TYPE").unwrap_or_else(|_| "unknown".into())
     }
+
+    /// Anchor `window`'s surface to the bottom of the screen as an
+    /// exclusive-zone layer surface — the taskbar's real panel role.
+    pub fn configure_as_panel(
+        display: RawDisplayHandle,
+        window: RawWindowHandle,
+        height: u32,
+        visible_on_all_workspaces: bool,
+    ) -> anyhow::Result<()> {
+        configure(
+            display,
+            window,
+            PanelRole::Panel { height },
+            visible_on_all_workspaces,
+        )
+    }
+
+    /// Anchor `window`'s surface above normal content without reserving
+    /// space — the command-bar/launcher overlay role.
+    pub fn configure_as_overlay(
+        display: RawDisplayHandle,
+        window: RawWindowHandle,
+        visible_on_all_workspaces: bool,
+    ) -> anyhow::Result<()> {
+        configure(display, window, PanelRole::Overlay, visible_on_all_workspaces)
+    }
+
+    fn configure(
+        display: RawDisplayHandle,
+        window: RawWindowHandle,
+        role: PanelRole,
+        visible_on_all_workspaces: bool,
+    ) -> anyhow::Result<()> {
+        if is_wayland() {
+            return configure_wayland(display, window, role, visible_on_all_workspaces);
+        }
+        if is_x11() {
+            return configure_x11_dock(window, role, visible_on_all_workspaces);
+        }
+        anyhow::bail!("neither Wayland nor X11 detected")
+    }
+
+    struct LayerShellState;
+    delegate_noop!(LayerShellState: ignore wl_compositor::WlCompositor);
+    delegate_noop!(LayerShellState: ignore wl_surface::WlSurface);
+    delegate_noop!(LayerShellState: ignore ZwlrLayerShellV1);
+
+    impl wayland_client::Dispatch<ZwlrLayerSurfaceV1, ()> for LayerShellState {
+        fn event(
+            _: &mut Self,
+            surface: &ZwlrLayerSurfaceV1,
+            event: zwlr_layer_surface_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &wayland_client::QueueHandle<Self>,
+        ) {
+            if let zwlr_layer_surface_v1::Event::Configure { serial, .. } = event {
+                surface.ack_configure(serial);
+            }
+        }
+    }
+
+    fn configure_wayland(
+        display: RawDisplayHandle,
+        window: RawWindowHandle,
+        role: PanelRole,
+        visible_on_all_workspaces: bool,
+    ) -> anyhow::Result<()> {
+        let RawDisplayHandle::Wayland(display_handle) = display else {
+            anyhow::bail!("window isn't backed by a Wayland display");
+        };
+        let RawWindowHandle::Wayland(window_handle) = window else {
+            anyhow::bail!("window isn't backed by a Wayland surface");
+        };
+
+        // SAFETY: the handles come from the live iced/winit window, which
+        // keeps the underlying `wl_display`/`wl_surface` alive for as long
+        // as this call runs.
+        let backend = unsafe {
+            wayland_client::backend::Backend::from_foreign_display(
+                display_handle.display.as_ptr().cast(),
+            )
+        };
+        let conn = Connection::from_backend(backend);
+        let surface_id = unsafe {
+            wayland_client::backend::ObjectId::from_ptr(
+                wl_surface::WlSurface::interface(),
+                window_handle.surface.as_ptr().cast(),
+            )?
+        };
+        let surface = wl_surface::WlSurface::from_id(&conn, surface_id)?;
+
+        let (globals, mut queue) = wayland_client::globals::registry_queue_init::<LayerShellState>(&conn)?;
+        let qh = queue.handle();
+        let layer_shell: ZwlrLayerShellV1 = globals.bind(&qh, 1..=4, ())?;
+
+        let (layer, exclusive_zone, anchor) = match role {
+            PanelRole::Panel { height } => (
+                zwlr_layer_shell_v1::Layer::Top,
+                height as i32,
+                Anchor::Bottom | Anchor::Left | Anchor::Right,
+            ),
+            PanelRole::Overlay => (
+                zwlr_layer_shell_v1::Layer::Overlay,
+                0,
+                Anchor::Top | Anchor::Left | Anchor::Right,
+            ),
+        };
+
+        let layer_surface = layer_shell.get_layer_surface(
+            &surface,
+            None,
+            layer,
+            "ai-ui-shell".to_string(),
+            &qh,
+            (),
+        );
+        layer_surface.set_anchor(anchor);
+        layer_surface.set_exclusive_zone(exclusive_zone);
+        layer_surface.set_keyboard_interactivity(KeyboardInteractivity::OnDemand);
+
+        // wlr-layer-shell surfaces live outside the workspace model
+        // entirely — they're compositor-global on Sway/Hyprland/COSMIC
+        // already, so there's nothing extra to request here.
+        let _ = visible_on_all_workspaces;
+
+        surface.commit();
+        queue.roundtrip(&mut LayerShellState)?;
+
+        // `queue` (and the `Connection`/layer-surface objects it keeps
+        // alive through the backend it holds) must not be dropped once
+        // this function returns, or the compositor sees the client go
+        // away and tears the surface down. Hand it to a dedicated thread
+        // that keeps pumping events — resize/close of the layer surface,
+        // output changes — for the life of the surface, the same way
+        // `ipc::serve` is kept running for the life of the process in
+        // `app.rs`.
+        std::thread::spawn(move || {
+            let _conn = conn;
+            loop {
+                if queue.blocking_dispatch(&mut LayerShellState).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    fn configure_x11_dock(
+        window: RawWindowHandle,
+        role: PanelRole,
+        visible_on_all_workspaces: bool,
+    ) -> anyhow::Result<()> {
+        let RawWindowHandle::Xlib(handle) = window else {
+            anyhow::bail!("window isn't backed by an X11 surface");
+        };
+
+        let (conn, _) = x11rb::connect(None)?;
+        let window_id = handle.window as u32;
+
+        let atom = |name: &str| -> anyhow::Result<u32> {
+            Ok(x11rb::protocol::xproto::intern_atom(&conn, false, name.as_bytes())?
+                .reply()?
+                .atom)
+        };
+
+        let net_wm_window_type = atom("_NET_WM_WINDOW_TYPE")?;
+        let net_wm_window_type_dock = atom("_NET_WM_WINDOW_TYPE_DOCK")?;
+        x11rb::protocol::xproto::change_property32(
+            &conn,
+            x11rb::protocol::xproto::PropMode::REPLACE,
+            window_id,
+            net_wm_window_type,
+            x11rb::protocol::xproto::AtomEnum::ATOM,
+            &[net_wm_window_type_dock],
+        )?;
+
+        if visible_on_all_workspaces {
+            let net_wm_state = atom("_NET_WM_STATE")?;
+            let net_wm_state_sticky = atom("_NET_WM_STATE_STICKY")?;
+            x11rb::protocol::xproto::change_property32(
+                &conn,
+                x11rb::protocol::xproto::PropMode::APPEND,
+                window_id,
+                net_wm_state,
+                x11rb::protocol::xproto::AtomEnum::ATOM,
+                &[net_wm_state_sticky],
+            )?;
+        }
+
+        // Panels additionally reserve screen space via `_NET_WM_STRUT`;
+        // overlays (command bar/launcher) skip this so they float above
+        // content instead of pushing it aside.
+        if let PanelRole::Panel { height } = role {
+            let net_wm_strut = atom("_NET_WM_STRUT")?;
+            // left, right, top, bottom
+            x11rb::protocol::xproto::change_property32(
+                &conn,
+                x11rb::protocol::xproto::PropMode::REPLACE,
+                window_id,
+                net_wm_strut,
+                x11rb::protocol::xproto::AtomEnum::CARDINAL,
+                &[0, 0, 0, height],
+            )?;
+        }
+
+        conn.flush()?;
+        Ok(())
+    }
 }
@@ -0,0 +1,57 @@
+//! Per-OS shell integration. Each submodule gates its own contents behind
+//! `#[cfg(target_os = "...")]` internally, so it's safe to declare all
+//! three unconditionally here.
+pub mod linux;
+pub mod macos;
+pub mod windows;
+
+use iced::window;
+use iced::Task;
+
+/// Configure `id`'s window as the taskbar panel once it has actually been
+/// created, reserving `height` logical pixels at the bottom of the
+/// screen. No-op on platforms without a panel integration.
+#[cfg(target_os = "linux")]
+pub fn configure_panel(id: window::Id, height: u32) -> Task<crate::app::Message> {
+    window::run_with_handle(id, move |handle| apply(handle, PanelKind::Panel(height)))
+        .map(crate::app::Message::PanelConfigured)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn configure_panel(_id: window::Id, _height: u32) -> Task<crate::app::Message> {
+    Task::none()
+}
+
+/// Configure `id`'s window as a floating overlay (command bar/launcher)
+/// once it has actually been created.
+#[cfg(target_os = "linux")]
+pub fn configure_overlay(id: window::Id) -> Task<crate::app::Message> {
+    window::run_with_handle(id, move |handle| apply(handle, PanelKind::Overlay))
+        .map(crate::app::Message::PanelConfigured)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn configure_overlay(_id: window::Id) -> Task<crate::app::Message> {
+    Task::none()
+}
+
+#[cfg(target_os = "linux")]
+enum PanelKind {
+    Panel(u32),
+    Overlay,
+}
+
+#[cfg(target_os = "linux")]
+fn apply<H>(handle: &H, kind: PanelKind) -> Result<(), String>
+where
+    H: raw_window_handle::HasDisplayHandle + raw_window_handle::HasWindowHandle,
+{
+    let display = handle.display_handle().map_err(|e| e.to_string())?.as_raw();
+    let window = handle.window_handle().map_err(|e| e.to_string())?.as_raw();
+
+    let result = match kind {
+        PanelKind::Panel(height) => linux::shell::configure_as_panel(display, window, height, true),
+        PanelKind::Overlay => linux::shell::configure_as_overlay(display, window, true),
+    };
+    result.map_err(|e| e.to_string())
+}
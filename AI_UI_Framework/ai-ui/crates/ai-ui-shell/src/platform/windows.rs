@@ -73,3 +73,153 @@ pub mod shell {
         set_as_shell("explorer.exe")
     }
 }
+
+#[cfg(windows)]
+pub mod service {
+    /// Supervises the Asgard GUI process as a Windows service
+    ///
+    /// Since `shell::set_as_shell` can replace explorer.exe, a crash of the
+    /// GUI process would otherwise leave the user with no desktop shell at
+    /// all. Running under this service keeps the SCM's eye on us: it
+    /// relaunches the shell executable after a short backoff whenever it
+    /// exits while the service is running. It does not check whether Asgard
+    /// is currently the registered shell before relaunching.
+    use std::ffi::OsString;
+    use std::path::Path;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use windows_service::service::{
+        ServiceAccess, ServiceControl, ServiceControlAccept, ServiceErrorControl,
+        ServiceExitCode, ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+    };
+    use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
+    use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    use windows_service::{define_windows_service, service_dispatcher};
+
+    const SERVICE_NAME: &str = "AsgardShellService";
+    const SERVICE_DISPLAY_NAME: &str = "Asgard Desktop Shell";
+    const SERVICE_TYPE: ServiceType = ServiceType::OWN_PROCESS;
+
+    /// How long to wait before relaunching a shell that exited unexpectedly
+    const RESTART_BACKOFF: Duration = Duration::from_secs(3);
+
+    define_windows_service!(ffi_service_main, service_main);
+
+    /// Register Asgard as an auto-start Windows service that launches
+    /// `exe_path --service` on boot
+    pub fn install_service(exe_path: &Path) -> windows_service::Result<()> {
+        let manager =
+            ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CREATE_SERVICE)?;
+
+        let service_info = ServiceInfo {
+            name: OsString::from(SERVICE_NAME),
+            display_name: OsString::from(SERVICE_DISPLAY_NAME),
+            service_type: SERVICE_TYPE,
+            start_type: ServiceStartType::AutoStart,
+            error_control: ServiceErrorControl::Normal,
+            executable_path: exe_path.to_path_buf(),
+            launch_arguments: vec![OsString::from("--service")],
+            dependencies: vec![],
+            account_name: None,
+            account_password: None,
+        };
+
+        let service = manager.create_service(&service_info, ServiceAccess::CHANGE_CONFIG)?;
+        service.set_description(
+            "Supervises the Asgard desktop shell and restarts it if it crashes.",
+        )?;
+        Ok(())
+    }
+
+    /// Stop and remove the Asgard service registration
+    pub fn uninstall_service() -> windows_service::Result<()> {
+        let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+        let service =
+            manager.open_service(SERVICE_NAME, ServiceAccess::DELETE | ServiceAccess::STOP)?;
+        let _ = service.stop();
+        service.delete()
+    }
+
+    /// Entry point when launched as `<exe> --service`; blocks until the SCM
+    /// stops the service
+    pub fn run_as_service() -> windows_service::Result<()> {
+        service_dispatcher::start(SERVICE_NAME, ffi_service_main)
+    }
+
+    fn service_main(_arguments: Vec<OsString>) {
+        if let Err(e) = run_service() {
+            tracing::error!("Asgard service exited with an error: {}", e);
+        }
+    }
+
+    fn run_service() -> windows_service::Result<()> {
+        let (shutdown_tx, shutdown_rx) = mpsc::channel();
+
+        let status_handle = service_control_handler::register(SERVICE_NAME, move |control| {
+            match control {
+                ServiceControl::Stop | ServiceControl::Shutdown => {
+                    let _ = shutdown_tx.send(());
+                    ServiceControlHandlerResult::NoError
+                }
+                ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+                _ => ServiceControlHandlerResult::NotImplemented,
+            }
+        })?;
+
+        report_status(&status_handle, ServiceState::StartPending, 1, 3000)?;
+
+        let exe_path = std::env::current_exe().unwrap_or_default();
+        let mut child = spawn_shell(&exe_path);
+        report_status(&status_handle, ServiceState::Running, 0, 0)?;
+
+        loop {
+            if shutdown_rx.recv_timeout(Duration::from_millis(500)).is_ok() {
+                break;
+            }
+
+            if let Some(child_proc) = child.as_mut() {
+                match child_proc.try_wait() {
+                    Ok(Some(status)) => {
+                        tracing::warn!("Asgard shell exited unexpectedly: {:?}", status);
+                        std::thread::sleep(RESTART_BACKOFF);
+                        child = spawn_shell(&exe_path);
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Failed to poll the shell process: {}", e),
+                }
+            }
+        }
+
+        report_status(&status_handle, ServiceState::StopPending, 1, 3000)?;
+        if let Some(mut child_proc) = child {
+            let _ = child_proc.kill();
+        }
+        report_status(&status_handle, ServiceState::Stopped, 0, 0)?;
+        Ok(())
+    }
+
+    fn spawn_shell(exe_path: &Path) -> Option<std::process::Child> {
+        std::process::Command::new(exe_path)
+            .spawn()
+            .map_err(|e| tracing::error!("Failed to launch the Asgard shell: {}", e))
+            .ok()
+    }
+
+    fn report_status(
+        handle: &service_control_handler::ServiceStatusHandle,
+        state: ServiceState,
+        checkpoint: u32,
+        wait_hint_ms: u32,
+    ) -> windows_service::Result<()> {
+        handle.set_service_status(ServiceStatus {
+            service_type: SERVICE_TYPE,
+            current_state: state,
+            controls_accepted: ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN,
+            exit_code: ServiceExitCode::Win32(0),
+            checkpoint,
+            wait_hint: Duration::from_millis(wait_hint_ms as u64),
+            process_id: None,
+        })
+    }
+}
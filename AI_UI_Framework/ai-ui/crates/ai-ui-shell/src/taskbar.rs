@@ -76,7 +76,19 @@ pub fn view<'a>(
     )
     .size(13);
 
-    let right = row![wifi, memory, battery, time_text].spacing(16);
+    let connectivity_badge = if !status.claude_reachable && !status.ollama_reachable {
+        Some("Offline")
+    } else if !status.claude_reachable {
+        Some("Local model only")
+    } else {
+        None
+    };
+    let badge: Element<Message> = match connectivity_badge {
+        Some(label) => text(label).size(13).into(),
+        None => text("").size(1).into(),
+    };
+
+    let right = row![badge, wifi, memory, battery, time_text].spacing(16);
 
     let bar = row![left, center, right]
         .spacing(8)
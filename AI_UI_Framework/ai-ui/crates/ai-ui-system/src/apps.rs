@@ -1,6 +1,10 @@
+use futures_util::Stream;
 use nucleo_matcher::pattern::{Atom, AtomKind, CaseMatching, Normalization};
 use nucleo_matcher::{Config, Matcher, Utf32Str};
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
 
 #[derive(Debug, Clone)]
 pub struct AppEntry {
@@ -8,27 +12,277 @@ pub struct AppEntry {
     pub exec: String,
     pub icon_path: Option<PathBuf>,
     pub description: Option<String>,
+    /// Desktop-entry id (Linux, e.g. `"firefox.desktop"`) or registry
+    /// ProgId (Windows) this app was resolved from, when known. Used to
+    /// match against `mimeapps.list`/`UserChoice` default-handler entries.
+    pub handler_id: Option<String>,
+    /// MIME types this app declares it can open (Linux `MimeType=`)
+    pub mime_types: Vec<String>,
+    /// File extensions this app is registered to open (Windows ProgIds)
+    pub extensions: Vec<String>,
+    /// Whether this entry must be run inside a terminal emulator
+    /// (desktop-entry `Terminal=true`)
+    pub terminal: bool,
+    /// Desktop Actions declared by this entry (e.g. Firefox's "New Private
+    /// Window"), exposed as secondary launch targets
+    pub actions: Vec<AppAction>,
+    /// How this app is packaged, which determines how `launch_by_name`
+    /// dispatches to it
+    pub source: AppSource,
 }
 
-/// Enumerate installed applications (cross-platform)
+/// Packaging kind an `AppEntry` was discovered through. Sandboxed
+/// packaging formats are launched via their own runner rather than by
+/// spawning `exec` directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppSource {
+    Native,
+    Flatpak,
+    Snap,
+    AppImage,
+}
+
+/// A Desktop Action declared by a `.desktop` entry — a secondary launch
+/// target alongside the application's main `exec`.
+#[derive(Debug, Clone)]
+pub struct AppAction {
+    pub name: String,
+    pub exec: String,
+}
+
+/// Enumerate installed applications (cross-platform). Collects the full
+/// result of [`enumerate_apps_stream`] for callers that don't need
+/// incremental progress.
 pub async fn enumerate_apps() -> anyhow::Result<Vec<AppEntry>> {
+    use futures_util::StreamExt;
+
+    let (mut stream, _progress) = enumerate_apps_stream();
     let mut apps = Vec::new();
+    while let Some(app) = stream.next().await {
+        apps.push(app);
+    }
+    Ok(apps)
+}
+
+/// Progress while enumerating installed applications: how many scan
+/// sources have completed out of how many are planned.
+#[derive(Debug, Clone, Copy)]
+pub struct ScanProgress {
+    pub scanned: usize,
+    pub total: usize,
+}
+
+/// Stream installed applications as they're discovered, rather than
+/// blocking until every source has been scanned, so a UI can render
+/// results incrementally while the scan continues. [`ScanProgress`]
+/// updates are delivered on the returned receiver alongside the app
+/// stream.
+///
+/// Entries are deduplicated by a directory-relative id (the desktop-file
+/// id on Linux, the Start-Menu-relative `.lnk` path on Windows, the
+/// bundle file name on macOS) so an app present in both a user and a
+/// system source is only yielded once, preferring whichever source is
+/// scanned first (XDG_DATA_HOME before the system dirs, the per-user
+/// Start Menu before the common one).
+pub fn enumerate_apps_stream() -> (impl Stream<Item = AppEntry>, mpsc::Receiver<ScanProgress>) {
+    let (app_tx, app_rx) = mpsc::channel::<AppEntry>(32);
+    let (progress_tx, progress_rx) = mpsc::channel::<ScanProgress>(8);
+
+    tokio::spawn(async move {
+        scan_apps(app_tx, progress_tx).await;
+    });
+
+    (receiver_stream(app_rx), progress_rx)
+}
+
+fn receiver_stream<T: Send + 'static>(mut rx: mpsc::Receiver<T>) -> impl Stream<Item = T> {
+    futures_util::stream::unfold(rx, |mut rx| async { rx.recv().await.map(|item| (item, rx)) })
+}
+
+async fn scan_apps(app_tx: mpsc::Sender<AppEntry>, progress_tx: mpsc::Sender<ScanProgress>) {
+    let mut seen: HashSet<String> = HashSet::new();
 
     #[cfg(target_os = "linux")]
     {
         use freedesktop_desktop_entry::{default_paths, get_languages_from_env, Iter};
 
         let locales = get_languages_from_env();
-        for entry in Iter::new(default_paths()).entries(Some(&locales)) {
-            if let (Some(name), Some(exec)) = (entry.name(&locales), entry.exec()) {
-                apps.push(AppEntry {
-                    name: name.to_string(),
-                    exec: exec.to_string(),
-                    icon_path: entry.icon().map(PathBuf::from),
-                    description: entry.comment(&locales).map(|c| c.to_string()),
-                });
+        let roots: Vec<PathBuf> = default_paths().collect();
+        let desktop_root_count = roots.len();
+        let total = desktop_root_count + 3; // + flatpak + snap + appimage
+
+        for (i, root) in roots.into_iter().enumerate() {
+            for entry in Iter::new([root]).entries(Some(&locales)) {
+                if !seen.insert(entry.id().to_string()) {
+                    continue;
+                }
+                if let (Some(name), Some(exec)) = (entry.name(&locales), entry.exec()) {
+                    let mime_types = entry
+                        .desktop_entry("MimeType")
+                        .map(|types| {
+                            types
+                                .split(';')
+                                .filter(|t| !t.is_empty())
+                                .map(|t| t.to_string())
+                                .collect()
+                        })
+                        .unwrap_or_default();
+
+                    let actions = entry
+                        .actions()
+                        .map(|ids| {
+                            ids.split(';')
+                                .filter(|id| !id.is_empty())
+                                .filter_map(|id| {
+                                    let name = entry.action_name(id, &locales)?;
+                                    let exec = entry.action_exec(id)?;
+                                    Some(AppAction {
+                                        name: name.to_string(),
+                                        exec: exec.to_string(),
+                                    })
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+
+                    let _ = app_tx
+                        .send(AppEntry {
+                            name: name.to_string(),
+                            exec: exec.to_string(),
+                            icon_path: entry.icon().map(PathBuf::from),
+                            description: entry.comment(&locales).map(|c| c.to_string()),
+                            handler_id: Some(entry.id().to_string()),
+                            mime_types,
+                            extensions: Vec::new(),
+                            terminal: entry.terminal(),
+                            actions,
+                            source: AppSource::Native,
+                        })
+                        .await;
+                }
+            }
+            let _ = progress_tx
+                .send(ScanProgress { scanned: i + 1, total })
+                .await;
+        }
+
+        // Flatpak apps, launched via `flatpak run <app-id>` rather than
+        // whatever Exec line a bundled desktop file might have
+        if let Ok(output) = std::process::Command::new("flatpak")
+            .args(["list", "--app", "--columns=application,name"])
+            .output()
+        {
+            for line in String::from_utf8_lossy(&output.stdout).lines() {
+                let mut fields = line.splitn(2, '\t');
+                let Some(app_id) = fields.next().map(str::trim).filter(|s| !s.is_empty()) else {
+                    continue;
+                };
+                let name = fields.next().map(str::trim).unwrap_or(app_id).to_string();
+
+                // Flatpak exports its desktop files under `<app-id>.desktop`,
+                // which the desktop-entry scan above already picked up under
+                // that same id — skip here so it doesn't show up twice.
+                if seen.contains(&format!("{app_id}.desktop")) {
+                    continue;
+                }
+
+                if seen.insert(format!("flatpak:{app_id}")) {
+                    let _ = app_tx
+                        .send(AppEntry {
+                            name,
+                            exec: format!("flatpak run {app_id}"),
+                            icon_path: None,
+                            description: None,
+                            handler_id: Some(app_id.to_string()),
+                            mime_types: Vec::new(),
+                            extensions: Vec::new(),
+                            terminal: false,
+                            actions: Vec::new(),
+                            source: AppSource::Flatpak,
+                        })
+                        .await;
+                }
+            }
+        }
+        let _ = progress_tx
+            .send(ScanProgress { scanned: desktop_root_count + 1, total })
+            .await;
+
+        // Snap apps, launched via `snap run <name>`
+        if let Ok(output) = std::process::Command::new("snap").arg("list").output() {
+            for line in String::from_utf8_lossy(&output.stdout).lines().skip(1) {
+                let Some(snap_name) = line.split_whitespace().next() else {
+                    continue;
+                };
+
+                if seen.insert(format!("snap:{snap_name}")) {
+                    let _ = app_tx
+                        .send(AppEntry {
+                            name: snap_name.to_string(),
+                            exec: format!("snap run {snap_name}"),
+                            icon_path: None,
+                            description: None,
+                            handler_id: Some(snap_name.to_string()),
+                            mime_types: Vec::new(),
+                            extensions: Vec::new(),
+                            terminal: false,
+                            actions: Vec::new(),
+                            source: AppSource::Snap,
+                        })
+                        .await;
+                }
             }
         }
+        let _ = progress_tx
+            .send(ScanProgress { scanned: desktop_root_count + 2, total })
+            .await;
+
+        // AppImages in common dirs, launched by executing the bundle directly
+        let appimage_dirs: Vec<PathBuf> = dirs::home_dir()
+            .map(|home| vec![home.join("Applications"), home.join(".local/bin")])
+            .unwrap_or_default();
+
+        for dir in appimage_dirs {
+            if let Ok(entries) = std::fs::read_dir(&dir) {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    let is_appimage = path
+                        .extension()
+                        .map(|e| e.eq_ignore_ascii_case("appimage"))
+                        .unwrap_or(false);
+                    if !is_appimage {
+                        continue;
+                    }
+
+                    let exec = path.to_string_lossy().to_string();
+                    if !seen.insert(format!("appimage:{exec}")) {
+                        continue;
+                    }
+
+                    let name = path
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let _ = app_tx
+                        .send(AppEntry {
+                            name,
+                            exec,
+                            icon_path: None,
+                            description: None,
+                            handler_id: None,
+                            mime_types: Vec::new(),
+                            extensions: Vec::new(),
+                            terminal: false,
+                            actions: Vec::new(),
+                            source: AppSource::AppImage,
+                        })
+                        .await;
+                }
+            }
+        }
+        let _ = progress_tx
+            .send(ScanProgress { scanned: desktop_root_count + 3, total })
+            .await;
     }
 
     #[cfg(windows)]
@@ -36,92 +290,162 @@ pub async fn enumerate_apps() -> anyhow::Result<Vec<AppEntry>> {
         use winreg::enums::*;
         use winreg::RegKey;
 
+        let start_menu_user = dirs::data_dir().map(|data_dir| {
+            data_dir
+                .parent()
+                .unwrap_or(&data_dir)
+                .join("Microsoft")
+                .join("Windows")
+                .join("Start Menu")
+                .join("Programs")
+        });
+        let start_menu_common = PathBuf::from(r"C:\ProgramData\Microsoft\Windows\Start Menu\Programs");
+
+        let total =
+            1 + start_menu_user.is_some() as usize + start_menu_common.exists() as usize;
+        let mut scanned = 0;
+
         let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-        if let Ok(uninstall) = hklm.open_subkey(
-            r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall",
-        ) {
+        if let Ok(uninstall) =
+            hklm.open_subkey(r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall")
+        {
             for key_name in uninstall.enum_keys().filter_map(|k| k.ok()) {
                 if let Ok(subkey) = uninstall.open_subkey(&key_name) {
                     if let Ok(name) = subkey.get_value::<String, _>("DisplayName") {
+                        if !seen.insert(format!("uninstall:{key_name}")) {
+                            continue;
+                        }
                         let exec = subkey
                             .get_value::<String, _>("InstallLocation")
                             .unwrap_or_default();
-                        apps.push(AppEntry {
-                            name,
-                            exec,
-                            icon_path: None,
-                            description: None,
-                        });
+                        let _ = app_tx
+                            .send(AppEntry {
+                                name,
+                                exec,
+                                icon_path: None,
+                                description: None,
+                                handler_id: None,
+                                mime_types: Vec::new(),
+                                extensions: Vec::new(),
+                            terminal: false,
+                            actions: Vec::new(),
+                            source: AppSource::Native,
+                            })
+                            .await;
                     }
                 }
             }
         }
+        scanned += 1;
+        let _ = progress_tx.send(ScanProgress { scanned, total }).await;
 
-        // Also scan Start Menu .lnk files for better coverage
-        if let Some(start_menu) = dirs::data_dir() {
-            let start_menu_path = start_menu
-                .parent()
-                .unwrap_or(&start_menu)
-                .join("Microsoft")
-                .join("Windows")
-                .join("Start Menu")
-                .join("Programs");
-            scan_start_menu(&start_menu_path, &mut apps);
+        if let Some(start_menu_path) = &start_menu_user {
+            for entry in scan_start_menu(start_menu_path) {
+                let id = format!("lnk:{}", entry.handler_id.as_deref().unwrap_or_default());
+                if seen.insert(id) {
+                    let _ = app_tx.send(entry).await;
+                }
+            }
+            scanned += 1;
+            let _ = progress_tx.send(ScanProgress { scanned, total }).await;
         }
 
-        // Also scan common Start Menu
-        let common_start = PathBuf::from(
-            r"C:\ProgramData\Microsoft\Windows\Start Menu\Programs",
-        );
-        if common_start.exists() {
-            scan_start_menu(&common_start, &mut apps);
+        if start_menu_common.exists() {
+            for entry in scan_start_menu(&start_menu_common) {
+                let id = format!("lnk:{}", entry.handler_id.as_deref().unwrap_or_default());
+                if seen.insert(id) {
+                    let _ = app_tx.send(entry).await;
+                }
+            }
+            scanned += 1;
+            let _ = progress_tx.send(ScanProgress { scanned, total }).await;
         }
     }
 
     #[cfg(target_os = "macos")]
     {
-        for dir in &["/Applications", "/System/Applications"] {
+        let roots = ["/Applications", "/System/Applications"];
+        let total = roots.len();
+
+        for (i, dir) in roots.iter().enumerate() {
             if let Ok(entries) = std::fs::read_dir(dir) {
                 for entry in entries.flatten() {
                     let path = entry.path();
                     if path.extension().map_or(false, |e| e == "app") {
+                        let relative_id = path
+                            .file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        if !seen.insert(relative_id) {
+                            continue;
+                        }
                         let name = path
                             .file_stem()
                             .map(|s| s.to_string_lossy().to_string())
                             .unwrap_or_default();
-                        apps.push(AppEntry {
-                            name,
-                            exec: path.to_string_lossy().to_string(),
-                            icon_path: None,
-                            description: None,
-                        });
+                        let _ = app_tx
+                            .send(AppEntry {
+                                name,
+                                exec: path.to_string_lossy().to_string(),
+                                icon_path: None,
+                                description: None,
+                                handler_id: None,
+                                mime_types: Vec::new(),
+                                extensions: Vec::new(),
+                            terminal: false,
+                            actions: Vec::new(),
+                            source: AppSource::Native,
+                            })
+                            .await;
                     }
                 }
             }
+            let _ = progress_tx
+                .send(ScanProgress { scanned: i + 1, total })
+                .await;
         }
     }
+}
 
-    Ok(apps)
+/// Recursively scan a Start Menu tree for `.lnk` shortcuts, tagging each
+/// with its path relative to `dir` so callers can dedupe the same shortcut
+/// found under both the per-user and common Start Menu roots.
+#[cfg(windows)]
+fn scan_start_menu(dir: &Path) -> Vec<AppEntry> {
+    let mut apps = Vec::new();
+    scan_start_menu_into(dir, dir, &mut apps);
+    apps
 }
 
 #[cfg(windows)]
-fn scan_start_menu(dir: &std::path::Path, apps: &mut Vec<AppEntry>) {
+fn scan_start_menu_into(root: &Path, dir: &Path, apps: &mut Vec<AppEntry>) {
     if let Ok(entries) = std::fs::read_dir(dir) {
         for entry in entries.flatten() {
             let path = entry.path();
             if path.is_dir() {
-                scan_start_menu(&path, apps);
+                scan_start_menu_into(root, &path, apps);
             } else if path.extension().map_or(false, |e| e == "lnk") {
                 let name = path
                     .file_stem()
                     .map(|s| s.to_string_lossy().to_string())
                     .unwrap_or_default();
                 if !name.is_empty() {
+                    let relative_id = path
+                        .strip_prefix(root)
+                        .unwrap_or(&path)
+                        .to_string_lossy()
+                        .to_string();
                     apps.push(AppEntry {
                         name,
                         exec: path.to_string_lossy().to_string(),
                         icon_path: None,
                         description: None,
+                        handler_id: Some(relative_id),
+                        mime_types: Vec::new(),
+                        extensions: Vec::new(),
+                            terminal: false,
+                            actions: Vec::new(),
+                            source: AppSource::Native,
                     });
                 }
             }
@@ -129,10 +453,22 @@ fn scan_start_menu(dir: &std::path::Path, apps: &mut Vec<AppEntry>) {
     }
 }
 
-/// Fuzzy search installed applications using nucleo (6x faster than skim)
+/// Fuzzy search installed applications using nucleo (6x faster than skim),
+/// folding in a frecency boost from past launches so frequently-used apps
+/// don't keep losing to closer string matches. With an empty query, apps
+/// are returned ordered by frecency alone.
 pub fn fuzzy_search(apps: &[AppEntry], query: &str) -> Vec<AppEntry> {
+    let history = LaunchHistory::load();
+
     if query.is_empty() {
-        return apps.to_vec();
+        let mut sorted = apps.to_vec();
+        sorted.sort_by(|a, b| {
+            history
+                .frecency(app_key(b))
+                .partial_cmp(&history.frecency(app_key(a)))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        return sorted;
     }
 
     let mut matcher = Matcher::new(Config::DEFAULT);
@@ -144,22 +480,286 @@ pub fn fuzzy_search(apps: &[AppEntry], query: &str) -> Vec<AppEntry> {
         false,
     );
 
-    let mut results: Vec<(i32, &AppEntry)> = apps
+    let mut results: Vec<(f64, &AppEntry)> = apps
         .iter()
         .filter_map(|app| {
             let mut buf = Vec::new();
             let haystack = Utf32Str::new(&app.name, &mut buf);
             atom.score(haystack, &mut matcher)
-                .map(|score| (score as i32, app))
+                .map(|score| (score as f64 + history.frecency(app_key(app)), app))
         })
         .collect();
 
-    results.sort_by(|a, b| b.0.cmp(&a.0));
+    results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
     results.into_iter().map(|(_, app)| app.clone()).collect()
 }
 
-/// Launch an application by name
-pub async fn launch_by_name(name: &str) -> anyhow::Result<()> {
+/// Stable identifier for an `AppEntry`, used as the key in the launch
+/// history. Prefers the desktop-entry id/ProgId when known since `exec`
+/// can vary across updates of the same app.
+fn app_key(app: &AppEntry) -> &str {
+    app.handler_id.as_deref().unwrap_or(&app.exec)
+}
+
+/// Find installed applications that can open `path`, ordered with the
+/// system default handler first and the rest alphabetically.
+#[cfg(target_os = "linux")]
+pub async fn apps_for_path(path: &Path) -> anyhow::Result<Vec<AppEntry>> {
+    let mime = mime_type_for_path(path)?;
+    let default_id = default_handler_for_mime(&mime);
+
+    let apps = enumerate_apps().await?;
+    let mut matches: Vec<AppEntry> = apps
+        .into_iter()
+        .filter(|app| app.mime_types.iter().any(|m| m == &mime))
+        .collect();
+
+    matches.sort_by(|a, b| {
+        let a_is_default = default_id.as_deref() == a.handler_id.as_deref();
+        let b_is_default = default_id.as_deref() == b.handler_id.as_deref();
+        b_is_default.cmp(&a_is_default).then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(matches)
+}
+
+#[cfg(target_os = "linux")]
+fn mime_type_for_path(path: &Path) -> anyhow::Result<String> {
+    let output = std::process::Command::new("xdg-mime")
+        .args(["query", "filetype", &path.to_string_lossy()])
+        .output()?;
+    let mime = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if mime.is_empty() {
+        anyhow::bail!("Could not determine MIME type for: {}", path.display());
+    }
+    Ok(mime)
+}
+
+#[cfg(target_os = "linux")]
+fn default_handler_for_mime(mime: &str) -> Option<String> {
+    let output = std::process::Command::new("xdg-mime")
+        .args(["query", "default", mime])
+        .output()
+        .ok()?;
+    let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if id.is_empty() {
+        None
+    } else {
+        Some(id)
+    }
+}
+
+/// Find installed applications that can open `path`, ordered with the
+/// system default handler first and the rest alphabetically.
+#[cfg(windows)]
+pub async fn apps_for_path(path: &Path) -> anyhow::Result<Vec<AppEntry>> {
+    use winreg::enums::*;
+    use winreg::RegKey;
+
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy().to_lowercase()))
+        .ok_or_else(|| anyhow::anyhow!("No file extension for: {}", path.display()))?;
+
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let file_exts_key = format!(r"Software\Microsoft\Windows\CurrentVersion\Explorer\FileExts\{ext}");
+
+    let default_progid = hkcu
+        .open_subkey(format!(r"{file_exts_key}\UserChoice"))
+        .and_then(|k| k.get_value::<String, _>("ProgId"))
+        .ok();
+
+    let mut progids: Vec<String> = Vec::new();
+    if let Ok(open_with) = hkcu.open_subkey(format!(r"{file_exts_key}\OpenWithProgids")) {
+        progids.extend(
+            open_with
+                .enum_values()
+                .filter_map(|v| v.ok())
+                .map(|(name, _)| name),
+        );
+    }
+    if let Ok(class_progid) = RegKey::predef(HKEY_CLASSES_ROOT)
+        .open_subkey(&ext)
+        .and_then(|k| k.get_value::<String, _>(""))
+    {
+        progids.push(class_progid);
+    }
+    progids.sort();
+    progids.dedup();
+
+    let hkcr = RegKey::predef(HKEY_CLASSES_ROOT);
+    let mut apps: Vec<AppEntry> = progids
+        .iter()
+        .filter_map(|progid| progid_to_app_entry(&hkcr, progid, &ext))
+        .collect();
+
+    apps.sort_by(|a, b| {
+        let a_is_default = default_progid.as_deref() == a.handler_id.as_deref();
+        let b_is_default = default_progid.as_deref() == b.handler_id.as_deref();
+        b_is_default.cmp(&a_is_default).then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(apps)
+}
+
+#[cfg(windows)]
+fn progid_to_app_entry(hkcr: &winreg::RegKey, progid: &str, ext: &str) -> Option<AppEntry> {
+    let progid_key = hkcr.open_subkey(progid).ok()?;
+    let name = progid_key
+        .get_value::<String, _>("FriendlyTypeName")
+        .or_else(|_| progid_key.get_value::<String, _>(""))
+        .unwrap_or_else(|_| progid.to_string());
+    let command = progid_key
+        .open_subkey(r"shell\open\command")
+        .and_then(|k| k.get_value::<String, _>(""))
+        .ok()?;
+
+    Some(AppEntry {
+        name,
+        exec: command,
+        icon_path: None,
+        description: None,
+        handler_id: Some(progid.to_string()),
+        mime_types: Vec::new(),
+        extensions: vec![ext.to_string()],
+        terminal: false,
+        actions: Vec::new(),
+        source: AppSource::Native,
+    })
+}
+
+/// Find installed applications that can open `path`, ordered with the
+/// system default handler first and the rest alphabetically.
+#[cfg(target_os = "macos")]
+pub async fn apps_for_path(path: &Path) -> anyhow::Result<Vec<AppEntry>> {
+    use core_foundation::base::TCFType;
+    use core_foundation::url::CFURL;
+    use core_services::{kLSRolesAll, LSCopyApplicationURLsForURL, LSCopyDefaultApplicationURLForURL};
+
+    let url = CFURL::from_path(path, false)
+        .ok_or_else(|| anyhow::anyhow!("Invalid path: {}", path.display()))?;
+
+    let default_app = unsafe {
+        let app_url =
+            LSCopyDefaultApplicationURLForURL(url.as_concrete_TypeRef(), kLSRolesAll, std::ptr::null_mut());
+        if app_url.is_null() {
+            None
+        } else {
+            let app_url: CFURL = TCFType::wrap_under_create_rule(app_url);
+            app_url.to_path()
+        }
+    };
+
+    let mut apps: Vec<AppEntry> = unsafe {
+        let array_ref = LSCopyApplicationURLsForURL(url.as_concrete_TypeRef(), kLSRolesAll);
+        if array_ref.is_null() {
+            Vec::new()
+        } else {
+            let urls: core_foundation::array::CFArray<CFURL> = TCFType::wrap_under_create_rule(array_ref);
+            urls.iter()
+                .filter_map(|app_url| {
+                    let bundle_path = app_url.to_path()?;
+                    let name = bundle_path.file_stem()?.to_string_lossy().to_string();
+                    Some(AppEntry {
+                        name,
+                        exec: bundle_path.to_string_lossy().to_string(),
+                        icon_path: None,
+                        description: None,
+                        handler_id: Some(bundle_path.to_string_lossy().to_string()),
+                        mime_types: Vec::new(),
+                        extensions: Vec::new(),
+                            terminal: false,
+                            actions: Vec::new(),
+                            source: AppSource::Native,
+                    })
+                })
+                .collect()
+        }
+    };
+
+    apps.sort_by(|a, b| {
+        let a_is_default = default_app.as_deref() == Some(Path::new(&a.exec));
+        let b_is_default = default_app.as_deref() == Some(Path::new(&b.exec));
+        b_is_default.cmp(&a_is_default).then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(apps)
+}
+
+/// Open `path` with a specific application, substituting the file path
+/// into the application's launch command.
+#[cfg(target_os = "linux")]
+pub async fn open_with(path: &Path, app: &AppEntry) -> anyhow::Result<()> {
+    let expanded = expand_desktop_field_codes(&app.exec, path);
+    let mut parts = expanded.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("No executable in exec string for: {}", app.name))?;
+
+    tracing::info!("Opening {} with {} ({})", path.display(), app.name, expanded);
+
+    let mut cmd = std::process::Command::new(program);
+    cmd.args(parts);
+    normalize_child_env(&mut cmd);
+    cmd.spawn()?;
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn expand_desktop_field_codes(exec: &str, path: &Path) -> String {
+    let file = path.to_string_lossy();
+    let mut expanded = String::new();
+    let mut chars = exec.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('f') | Some('F') | Some('u') | Some('U') => expanded.push_str(&file),
+            Some('%') => expanded.push('%'),
+            Some(_) => {} // drop field codes we don't substitute (%i, %c, %k, ...)
+            None => expanded.push('%'),
+        }
+    }
+    expanded
+}
+
+/// Open `path` with a specific application, substituting the file path
+/// into the application's registered `shell\open\command` string.
+#[cfg(windows)]
+pub async fn open_with(path: &Path, app: &AppEntry) -> anyhow::Result<()> {
+    let path_str = path.to_string_lossy();
+    let command = if app.exec.contains("%1") {
+        app.exec.replace("%1", &path_str)
+    } else {
+        format!("{} \"{}\"", app.exec, path_str)
+    };
+
+    tracing::info!("Opening {} with {} ({})", path.display(), app.name, command);
+
+    let mut cmd = std::process::Command::new("cmd");
+    cmd.args(["/C", "start", "", &command]);
+    normalize_child_env(&mut cmd);
+    cmd.spawn()?;
+    Ok(())
+}
+
+/// Open `path` with a specific application via `open -a`.
+#[cfg(target_os = "macos")]
+pub async fn open_with(path: &Path, app: &AppEntry) -> anyhow::Result<()> {
+    tracing::info!("Opening {} with {}", path.display(), app.name);
+
+    let mut cmd = std::process::Command::new("open");
+    cmd.args(["-a", &app.exec, &path.to_string_lossy()]);
+    normalize_child_env(&mut cmd);
+    cmd.spawn()?;
+    Ok(())
+}
+
+/// Launch an application by name, optionally targeting one of its Desktop
+/// Actions (e.g. `Some("New Private Window")`) instead of the main `exec`.
+pub async fn launch_by_name(name: &str, action: Option<&str>) -> anyhow::Result<()> {
     let apps = enumerate_apps().await?;
 
     let results = fuzzy_search(&apps, name);
@@ -167,16 +767,30 @@ pub async fn launch_by_name(name: &str) -> anyhow::Result<()> {
         .first()
         .ok_or_else(|| anyhow::anyhow!("No app found matching: {}", name))?;
 
-    tracing::info!("Launching app: {} ({})", app.name, app.exec);
+    let target_exec = match action {
+        Some(action_name) => {
+            &app.actions
+                .iter()
+                .find(|a| a.name.eq_ignore_ascii_case(action_name))
+                .ok_or_else(|| anyhow::anyhow!("No action '{}' for: {}", action_name, app.name))?
+                .exec
+        }
+        None => &app.exec,
+    };
+
+    tracing::info!("Launching app: {} ({})", app.name, target_exec);
 
     #[cfg(windows)]
     {
-        if app.exec.ends_with(".lnk") {
-            std::process::Command::new("cmd")
-                .args(["/C", "start", "", &app.exec])
-                .spawn()?;
-        } else if !app.exec.is_empty() {
-            std::process::Command::new(&app.exec).spawn()?;
+        if target_exec.ends_with(".lnk") {
+            let mut cmd = std::process::Command::new("cmd");
+            cmd.args(["/C", "start", "", target_exec]);
+            normalize_child_env(&mut cmd);
+            cmd.spawn()?;
+        } else if !target_exec.is_empty() {
+            let mut cmd = std::process::Command::new(target_exec);
+            normalize_child_env(&mut cmd);
+            cmd.spawn()?;
         } else {
             anyhow::bail!("No executable path for: {}", app.name);
         }
@@ -184,9 +798,381 @@ pub async fn launch_by_name(name: &str) -> anyhow::Result<()> {
 
     #[cfg(not(windows))]
     {
-        let exec = app.exec.split_whitespace().next().unwrap_or(&app.exec);
-        std::process::Command::new(exec).spawn()?;
+        // AppImage `exec` is a literal filesystem path (which may contain
+        // spaces) rather than a shell-style command line — every other
+        // source's exec is already a plain `program arg...` string, safe
+        // to tokenize on whitespace. Unexpanded desktop-entry field codes
+        // (`%U`, `%f`, ...) are dropped since no file is being passed.
+        let mut cmd = if app.source == AppSource::AppImage {
+            if app.terminal {
+                let terminal = detect_terminal().ok_or_else(|| {
+                    anyhow::anyhow!("No terminal emulator found to run: {}", app.name)
+                })?;
+                build_terminal_command(&terminal, target_exec, &[])
+            } else {
+                std::process::Command::new(target_exec)
+            }
+        } else {
+            let mut tokens = target_exec.split_whitespace();
+            let program = tokens.next().unwrap_or(target_exec);
+            let args: Vec<&str> = tokens
+                .filter(|t| !(t.len() == 2 && t.starts_with('%')))
+                .collect();
+
+            if app.terminal {
+                let terminal = detect_terminal().ok_or_else(|| {
+                    anyhow::anyhow!("No terminal emulator found to run: {}", app.name)
+                })?;
+                build_terminal_command(&terminal, program, &args)
+            } else {
+                let mut cmd = std::process::Command::new(program);
+                cmd.args(&args);
+                cmd
+            }
+        };
+        normalize_child_env(&mut cmd);
+        cmd.spawn()?;
+    }
+
+    let mut history = LaunchHistory::load();
+    history.record_launch(app_key(app));
+    if let Err(e) = history.save() {
+        tracing::warn!("Failed to persist launch history: {}", e);
     }
 
     Ok(())
 }
+
+/// Resolve which terminal emulator to wrap `Terminal=true` entries in:
+/// `$TERMINAL` if set, otherwise the first of a fallback probe list found
+/// on `$PATH`.
+#[cfg(not(windows))]
+fn detect_terminal() -> Option<String> {
+    if let Ok(term) = std::env::var("TERMINAL") {
+        if !term.is_empty() {
+            return Some(term);
+        }
+    }
+
+    const FALLBACKS: &[&str] = &["foot", "alacritty", "kitty", "gnome-terminal", "x-terminal-emulator"];
+    FALLBACKS
+        .iter()
+        .find(|candidate| command_exists(candidate))
+        .map(|candidate| candidate.to_string())
+}
+
+#[cfg(not(windows))]
+fn command_exists(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
+}
+
+/// Build the command to run `program args` inside `terminal`.
+#[cfg(not(windows))]
+fn build_terminal_command(terminal: &str, program: &str, args: &[&str]) -> std::process::Command {
+    let mut cmd = std::process::Command::new(terminal);
+    // gnome-terminal takes the command after a `--` separator rather than `-e`
+    if terminal == "gnome-terminal" {
+        cmd.arg("--");
+    } else {
+        cmd.arg("-e");
+    }
+    cmd.arg(program).args(args);
+    cmd
+}
+
+/// Whether the current process is running inside a Flatpak sandbox.
+pub fn is_flatpak() -> bool {
+    std::env::var_os("FLATPAK_ID").is_some() || Path::new("/.flatpak-info").exists()
+}
+
+/// Whether the current process is running inside a Snap confinement.
+pub fn is_snap() -> bool {
+    std::env::var_os("SNAP").is_some()
+}
+
+/// Whether the current process is running as (or inside) an AppImage.
+pub fn is_appimage() -> bool {
+    std::env::var_os("APPDIR").is_some() || std::env::var_os("APPIMAGE").is_some()
+}
+
+fn is_sandboxed() -> bool {
+    is_flatpak() || is_snap() || is_appimage()
+}
+
+/// Environment variables that AppImage/Flatpak/Snap runtimes point at the
+/// bundle's own copies of the dynamic linker and GTK/GStreamer plugin
+/// loaders. A launched application must not inherit these.
+const SANDBOX_INJECTED_VARS: &[&str] = &[
+    "LD_LIBRARY_PATH",
+    "GST_PLUGIN_SYSTEM_PATH_1_0",
+    "GST_PLUGIN_SCANNER",
+    "GTK_PATH",
+    "GTK_EXE_PREFIX",
+    "GTK_DATA_PREFIX",
+];
+
+/// Colon-list variables that sandbox runtimes prepend their own entries to
+/// rather than fully replacing, so they need de-duplication instead of an
+/// outright strip.
+const SANDBOX_PATHLIST_VARS: &[&str] = &["PATH", "XDG_DATA_DIRS"];
+
+/// Directory prefixes the current sandbox runtime owns, i.e. the bundle's
+/// own mount point rather than the host system. There is no pre-sandbox
+/// environment snapshot to restore (the runtime's wrapper script prepends
+/// these before our process even starts), so `normalize_pathlist` strips
+/// entries under these prefixes directly instead.
+fn sandbox_bundle_prefixes() -> Vec<String> {
+    let mut prefixes = Vec::new();
+    if let Some(appdir) = std::env::var_os("APPDIR") {
+        prefixes.push(appdir.to_string_lossy().into_owned());
+    }
+    if is_flatpak() {
+        prefixes.push("/app".to_string());
+    }
+    if let Some(snap) = std::env::var_os("SNAP") {
+        prefixes.push(snap.to_string_lossy().into_owned());
+    }
+    prefixes
+}
+
+/// Clean a colon-separated path list for a sandboxed process: drop entries
+/// under the current sandbox's own bundle directory (`$APPDIR`, `/app` for
+/// Flatpak, `$SNAP`), then de-duplicate what's left, keeping the later
+/// (lower priority) occurrence of any path that still appears more than
+/// once. Returns `None` if nothing survives, so the caller can unset the
+/// variable instead of emitting an empty one.
+pub fn normalize_pathlist(value: &str) -> Option<String> {
+    normalize_pathlist_with_prefixes(value, &sandbox_bundle_prefixes())
+}
+
+fn normalize_pathlist_with_prefixes(value: &str, bundle_prefixes: &[String]) -> Option<String> {
+    let entries: Vec<&str> = value
+        .split(':')
+        .filter(|e| !e.is_empty())
+        .filter(|e| {
+            !bundle_prefixes
+                .iter()
+                .any(|prefix| Path::new(e).starts_with(Path::new(prefix)))
+        })
+        .collect();
+
+    let mut last_index = std::collections::HashMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        last_index.insert(*entry, i);
+    }
+
+    let normalized: Vec<&str> = entries
+        .iter()
+        .enumerate()
+        .filter(|(i, entry)| last_index.get(*entry) == Some(i))
+        .map(|(_, entry)| *entry)
+        .collect();
+
+    if normalized.is_empty() {
+        None
+    } else {
+        Some(normalized.join(":"))
+    }
+}
+
+/// Strip sandbox-injected environment entries before spawning a child, so
+/// an application launched from inside an AppImage/Flatpak/Snap starts in
+/// a clean system environment rather than inheriting the bundle's own
+/// linker and plugin search paths. No-op outside a sandbox.
+fn normalize_child_env(cmd: &mut std::process::Command) {
+    if !is_sandboxed() {
+        return;
+    }
+
+    for var in SANDBOX_INJECTED_VARS {
+        cmd.env_remove(var);
+    }
+
+    for var in SANDBOX_PATHLIST_VARS {
+        if let Some(value) = std::env::var_os(var) {
+            match normalize_pathlist(&value.to_string_lossy()) {
+                Some(normalized) => {
+                    cmd.env(var, normalized);
+                }
+                None => {
+                    cmd.env_remove(var);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct UsageEntry {
+    count: u32,
+    last_launched_at: i64,
+}
+
+/// Persisted launch counts and timestamps, keyed by `app_key`, used to fold
+/// a frecency boost into `fuzzy_search`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LaunchHistory {
+    entries: HashMap<String, UsageEntry>,
+}
+
+impl LaunchHistory {
+    /// Load the persisted launch history from the user data dir, or an
+    /// empty history if none exists yet.
+    fn load() -> Self {
+        std::fs::read_to_string(history_path())
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Persist the launch history to the user data dir.
+    fn save(&self) -> anyhow::Result<()> {
+        let path = history_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Record a successful launch, bumping the count and refreshing the
+    /// last-launched timestamp.
+    fn record_launch(&mut self, key: &str) {
+        let entry = self.entries.entry(key.to_string()).or_default();
+        entry.count += 1;
+        entry.last_launched_at = now_unix();
+    }
+
+    /// Frecency score: launch count weighted by recency decay.
+    fn frecency(&self, key: &str) -> f64 {
+        let Some(entry) = self.entries.get(key) else {
+            return 0.0;
+        };
+        entry.count as f64 * decay(now_unix() - entry.last_launched_at)
+    }
+}
+
+fn history_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("ai-ui")
+        .join("launch_history.json")
+}
+
+fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Bucketed recency decay: apps launched in the last hour are weighted
+/// heavily, fading to zero after about a month of disuse.
+fn decay(age_secs: i64) -> f64 {
+    const HOUR: i64 = 3600;
+    const DAY: i64 = 24 * HOUR;
+    const WEEK: i64 = 7 * DAY;
+    const MONTH: i64 = 30 * DAY;
+
+    match age_secs {
+        a if a < HOUR => 4.0,
+        a if a < DAY => 2.0,
+        a if a < WEEK => 1.0,
+        a if a < MONTH => 0.5,
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_pathlist_dedups_keeping_later_occurrence() {
+        let result = normalize_pathlist_with_prefixes("/a:/b:/a:/c", &[]);
+        assert_eq!(result.as_deref(), Some("/b:/a:/c"));
+    }
+
+    #[test]
+    fn normalize_pathlist_drops_sandbox_bundle_prefix() {
+        let result = normalize_pathlist_with_prefixes(
+            "/tmp/.mount_AppAbc/usr/bin:/usr/bin:/usr/local/bin",
+            &["/tmp/.mount_AppAbc".to_string()],
+        );
+        assert_eq!(result.as_deref(), Some("/usr/bin:/usr/local/bin"));
+    }
+
+    #[test]
+    fn normalize_pathlist_keeps_sibling_directories_sharing_a_text_prefix() {
+        // `/app` as a bundle prefix must not match `/application/lib` or
+        // `/app-data/bin` — those are unrelated host paths that merely
+        // share a string prefix, not path components.
+        let result = normalize_pathlist_with_prefixes(
+            "/app/bin:/application/lib:/app-data/bin:/usr/bin",
+            &["/app".to_string()],
+        );
+        assert_eq!(
+            result.as_deref(),
+            Some("/application/lib:/app-data/bin:/usr/bin")
+        );
+    }
+
+    #[test]
+    fn normalize_pathlist_ignores_empty_segments() {
+        let result = normalize_pathlist_with_prefixes("/usr/bin::/usr/local/bin:", &[]);
+        assert_eq!(result.as_deref(), Some("/usr/bin:/usr/local/bin"));
+    }
+
+    #[test]
+    fn normalize_pathlist_returns_none_when_everything_is_stripped() {
+        let result = normalize_pathlist_with_prefixes("/app/bin:/app/lib", &["/app".to_string()]);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn decay_fades_with_age() {
+        assert_eq!(decay(0), 4.0);
+        assert_eq!(decay(3600), 2.0);
+        assert_eq!(decay(24 * 3600), 1.0);
+        assert_eq!(decay(7 * 24 * 3600), 0.5);
+        assert_eq!(decay(30 * 24 * 3600), 0.0);
+    }
+
+    #[test]
+    fn frecency_is_zero_for_unknown_key() {
+        let history = LaunchHistory::default();
+        assert_eq!(history.frecency("never-launched"), 0.0);
+    }
+
+    #[test]
+    fn frecency_ranks_a_recently_launched_app_above_a_stale_one() {
+        let mut history = LaunchHistory::default();
+        history.entries.insert(
+            "recent".to_string(),
+            UsageEntry {
+                count: 1,
+                last_launched_at: now_unix(),
+            },
+        );
+        history.entries.insert(
+            "stale".to_string(),
+            UsageEntry {
+                count: 1,
+                last_launched_at: now_unix() - 60 * 24 * 3600,
+            },
+        );
+        assert!(history.frecency("recent") > history.frecency("stale"));
+    }
+
+    #[test]
+    fn record_launch_bumps_count_and_refreshes_timestamp() {
+        let mut history = LaunchHistory::default();
+        history.record_launch("firefox");
+        history.record_launch("firefox");
+        let entry = &history.entries["firefox"];
+        assert_eq!(entry.count, 2);
+        assert!(entry.last_launched_at > 0);
+    }
+}
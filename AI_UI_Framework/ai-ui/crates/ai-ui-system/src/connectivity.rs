@@ -0,0 +1,88 @@
+/// Wi-Fi / Bluetooth / airplane-mode control
+///
+/// Built on the WinRT `Windows.Devices.Radios` surface so toggles act on
+/// the same radios Windows itself manages, instead of scraping `netsh`
+/// output or hard-coding connectivity state.
+#[cfg(windows)]
+pub mod radios {
+    use anyhow::Context;
+    use windows::Devices::Radios::{Radio, RadioAccessStatus, RadioKind, RadioState};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum RadioType {
+        WiFi,
+        Bluetooth,
+        Cellular,
+        Other,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct RadioInfo {
+        pub name: String,
+        pub kind: RadioType,
+        pub on: bool,
+    }
+
+    fn to_radio_type(kind: RadioKind) -> RadioType {
+        match kind {
+            RadioKind::WiFi => RadioType::WiFi,
+            RadioKind::Bluetooth => RadioType::Bluetooth,
+            RadioKind::MobileBroadband => RadioType::Cellular,
+            _ => RadioType::Other,
+        }
+    }
+
+    /// Enumerate every radio the OS exposes, requesting access first
+    pub async fn list_radios() -> anyhow::Result<Vec<(Radio, RadioInfo)>> {
+        let access = Radio::RequestAccessAsync()?.await?;
+        if access != RadioAccessStatus::Allowed {
+            anyhow::bail!("radio access was denied ({:?})", access);
+        }
+
+        let radios = Radio::GetRadiosAsync()?.await?;
+        let mut result = Vec::with_capacity(radios.Size()? as usize);
+        for radio in radios {
+            let info = RadioInfo {
+                name: radio.Name()?.to_string(),
+                kind: to_radio_type(radio.Kind()?),
+                on: radio.State()? == RadioState::On,
+            };
+            result.push((radio, info));
+        }
+        Ok(result)
+    }
+
+    /// Set every radio of `kind` to on/off
+    pub async fn set_state(kind: RadioType, on: bool) -> anyhow::Result<()> {
+        let target = if on { RadioState::On } else { RadioState::Off };
+        for (radio, info) in list_radios().await? {
+            if info.kind == kind {
+                radio
+                    .SetStateAsync(target)?
+                    .await
+                    .with_context(|| format!("failed to set {} to {:?}", info.name, target))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flip every radio of `kind` based on whether any of them are currently on.
+    /// Returns the new state.
+    pub async fn toggle(kind: RadioType) -> anyhow::Result<bool> {
+        let radios = list_radios().await?;
+        let currently_on = radios.iter().any(|(_, info)| info.kind == kind && info.on);
+        set_state(kind, !currently_on).await?;
+        Ok(!currently_on)
+    }
+
+    /// Airplane mode: turn every known radio off, or restore them all on
+    pub async fn set_airplane_mode(enabled: bool) -> anyhow::Result<()> {
+        let target = if enabled { RadioState::Off } else { RadioState::On };
+        for (radio, info) in list_radios().await? {
+            if let Err(e) = radio.SetStateAsync(target)?.await {
+                tracing::warn!("failed to set {} state: {}", info.name, e);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+/// Platform-agnostic input injection and media control
+///
+/// Replaces shelling out to PowerShell's `SendKeys` for volume/media keys
+/// with native input injection, and fills in the brightness actions the
+/// `system_command` tool advertises but never implemented.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputAction {
+    VolumeUp,
+    VolumeDown,
+    Mute,
+    MediaPlayPause,
+    MediaNext,
+    MediaPrevious,
+    BrightnessUp,
+    BrightnessDown,
+}
+
+/// A backend that can carry out a single `InputAction` on the current platform
+pub trait InputBackend {
+    /// Dispatch the action, returning a short human-readable result
+    fn dispatch(&self, action: InputAction) -> anyhow::Result<String>;
+}
+
+/// Construct the input backend for the current platform
+#[cfg(windows)]
+pub fn backend() -> impl InputBackend {
+    windows_backend::WindowsInputBackend
+}
+
+#[cfg(target_os = "linux")]
+pub fn backend() -> impl InputBackend {
+    linux_backend::LinuxInputBackend
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn backend() -> impl InputBackend {
+    unsupported_backend::UnsupportedInputBackend
+}
+
+#[cfg(windows)]
+mod windows_backend {
+    use super::{InputAction, InputBackend};
+    use windows::Win32::Devices::Display::{
+        DestroyPhysicalMonitors, GetMonitorBrightness, GetNumberOfPhysicalMonitorsFromHMONITOR,
+        GetPhysicalMonitorsFromHMONITOR, PHYSICAL_MONITOR, SetMonitorBrightness,
+    };
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Gdi::{MonitorFromWindow, MONITOR_DEFAULTTOPRIMARY};
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, VIRTUAL_KEY,
+        VK_MEDIA_NEXT_TRACK, VK_MEDIA_PLAY_PAUSE, VK_MEDIA_PREV_TRACK, VK_VOLUME_DOWN,
+        VK_VOLUME_MUTE, VK_VOLUME_UP,
+    };
+
+    pub struct WindowsInputBackend;
+
+    impl InputBackend for WindowsInputBackend {
+        fn dispatch(&self, action: InputAction) -> anyhow::Result<String> {
+            let vk = match action {
+                InputAction::VolumeUp => VK_VOLUME_UP,
+                InputAction::VolumeDown => VK_VOLUME_DOWN,
+                InputAction::Mute => VK_VOLUME_MUTE,
+                InputAction::MediaPlayPause => VK_MEDIA_PLAY_PAUSE,
+                InputAction::MediaNext => VK_MEDIA_NEXT_TRACK,
+                InputAction::MediaPrevious => VK_MEDIA_PREV_TRACK,
+                InputAction::BrightnessUp | InputAction::BrightnessDown => {
+                    return adjust_brightness(action);
+                }
+            };
+
+            send_key(vk)?;
+            Ok(describe(action))
+        }
+    }
+
+    fn send_key(vk: VIRTUAL_KEY) -> anyhow::Result<()> {
+        let mut key_down = INPUT::default();
+        key_down.r#type = INPUT_KEYBOARD;
+        key_down.Anonymous.ki = KEYBDINPUT {
+            wVk: vk,
+            ..Default::default()
+        };
+
+        let mut key_up = INPUT::default();
+        key_up.r#type = INPUT_KEYBOARD;
+        key_up.Anonymous.ki = KEYBDINPUT {
+            wVk: vk,
+            dwFlags: KEYEVENTF_KEYUP,
+            ..Default::default()
+        };
+
+        let inputs = [key_down, key_up];
+        let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+        if sent as usize != inputs.len() {
+            anyhow::bail!("SendInput only delivered {} of {} events", sent, inputs.len());
+        }
+        Ok(())
+    }
+
+    /// Step the primary monitor's DDC/CI brightness up or down
+    fn adjust_brightness(action: InputAction) -> anyhow::Result<String> {
+        unsafe {
+            let hmonitor = MonitorFromWindow(HWND::default(), MONITOR_DEFAULTTOPRIMARY);
+
+            let mut count = 0u32;
+            GetNumberOfPhysicalMonitorsFromHMONITOR(hmonitor, &mut count)?;
+            if count == 0 {
+                anyhow::bail!("no physical monitors expose brightness control");
+            }
+
+            let mut monitors = vec![PHYSICAL_MONITOR::default(); count as usize];
+            GetPhysicalMonitorsFromHMONITOR(hmonitor, &mut monitors)?;
+
+            let monitor = monitors[0].hPhysicalMonitor;
+            let (mut min, mut current, mut max) = (0u32, 0u32, 0u32);
+            GetMonitorBrightness(monitor, &mut min, &mut current, &mut max)?;
+
+            let step = ((max - min) / 20).max(1);
+            let next = match action {
+                InputAction::BrightnessUp => (current + step).min(max),
+                InputAction::BrightnessDown => current.saturating_sub(step).max(min),
+                _ => unreachable!("only brightness actions reach adjust_brightness"),
+            };
+            SetMonitorBrightness(monitor, next)?;
+
+            let _ = DestroyPhysicalMonitors(&monitors);
+
+            Ok(format!("Brightness set to {}%", next))
+        }
+    }
+
+    fn describe(action: InputAction) -> String {
+        match action {
+            InputAction::VolumeUp => "Volume increased",
+            InputAction::VolumeDown => "Volume decreased",
+            InputAction::Mute => "Volume muted/unmuted",
+            InputAction::MediaPlayPause => "Playback toggled",
+            InputAction::MediaNext => "Skipped to next track",
+            InputAction::MediaPrevious => "Skipped to previous track",
+            InputAction::BrightnessUp | InputAction::BrightnessDown => unreachable!(),
+        }
+        .into()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux_backend {
+    use super::{InputAction, InputBackend};
+
+    /// Linux input injection (uinput) and brightness (sysfs/backlight) are
+    /// not wired up yet — this keeps the trait boundary in place so a
+    /// uinput/keysym backend can be dropped in without touching callers.
+    pub struct LinuxInputBackend;
+
+    impl InputBackend for LinuxInputBackend {
+        fn dispatch(&self, action: InputAction) -> anyhow::Result<String> {
+            anyhow::bail!("{:?} is not yet implemented on Linux", action)
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+mod unsupported_backend {
+    use super::{InputAction, InputBackend};
+
+    pub struct UnsupportedInputBackend;
+
+    impl InputBackend for UnsupportedInputBackend {
+        fn dispatch(&self, action: InputAction) -> anyhow::Result<String> {
+            anyhow::bail!("{:?} is not supported on this platform", action)
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+/// Local IPC server
+///
+/// Lets companion processes talk to the running shell over a local
+/// transport — a named pipe on Windows, a Unix domain socket elsewhere —
+/// using length-prefixed framing: each message is a 4-byte little-endian
+/// length header followed by a JSON payload. Connections are multiplexed:
+/// a reader task parses frames as they complete and a writer task drains a
+/// channel, so several requests can be in flight on one connection at
+/// once, matched back up by `id`.
+#[cfg(unix)]
+const SOCKET_NAME: &str = "ai-ui-shell.sock";
+#[cfg(windows)]
+const PIPE_NAME: &str = r"\\.\pipe\ai-ui-shell";
+
+/// Largest frame payload we'll allocate for, regardless of what a
+/// connecting process claims in the length prefix. Requests and tool
+/// results are small JSON blobs; this is generous headroom over that,
+/// not a real capacity limit.
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params")]
+pub enum IpcMethod {
+    GetStatus,
+    ExecuteAction {
+        action: String,
+    },
+    ListWindows,
+    RegisterTool {
+        name: String,
+        description: String,
+        input_schema: serde_json::Value,
+    },
+    /// Sent by the shell back to a connection that registered a tool, to
+    /// actually invoke it.
+    CallTool {
+        name: String,
+        arguments: serde_json::Value,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpcRequest {
+    pub id: u64,
+    pub method: IpcMethod,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+pub enum IpcResult {
+    Status(crate::status::SystemStatus),
+    ActionResult(String),
+    Windows(Vec<String>),
+    ToolRegistered,
+    ToolResult(String),
+    Error(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpcResponse {
+    pub id: u64,
+    pub result: IpcResult,
+}
+
+/// The write side of a connection, kept around so the shell can call back
+/// into whichever process registered a tool and await its reply by id —
+/// the same correlation pattern `McpSession` uses for MCP servers.
+struct OutboundConnection {
+    sender: mpsc::Sender<Vec<u8>>,
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<u64, oneshot::Sender<IpcResult>>>,
+}
+
+impl OutboundConnection {
+    async fn call_tool(&self, name: &str, arguments: serde_json::Value) -> anyhow::Result<String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id, tx);
+
+        let request = IpcRequest {
+            id,
+            method: IpcMethod::CallTool {
+                name: name.to_string(),
+                arguments,
+            },
+        };
+        let bytes = serde_json::to_vec(&request)?;
+        self.sender
+            .send(bytes)
+            .await
+            .map_err(|_| anyhow::anyhow!("IPC connection closed"))?;
+
+        match rx
+            .await
+            .map_err(|_| anyhow::anyhow!("IPC connection closed before reply"))?
+        {
+            IpcResult::ToolResult(text) => Ok(text),
+            IpcResult::Error(e) => Err(anyhow::anyhow!(e)),
+            other => Err(anyhow::anyhow!("unexpected IPC reply: {:?}", other)),
+        }
+    }
+}
+
+/// A tool an external process contributed over `RegisterTool`
+#[derive(Clone)]
+pub struct RegisteredTool {
+    pub name: String,
+    pub description: String,
+    pub input_schema: serde_json::Value,
+    connection: Arc<OutboundConnection>,
+}
+
+/// Tools contributed by connected IPC clients, merged into Claude's tool set
+#[derive(Default)]
+pub struct ToolRegistry {
+    tools: Mutex<HashMap<String, RegisteredTool>>,
+}
+
+impl ToolRegistry {
+    async fn register(&self, tool: RegisteredTool) {
+        tracing::info!("Registered external IPC tool: {}", tool.name);
+        self.tools.lock().await.insert(tool.name.clone(), tool);
+    }
+
+    /// Snapshot of registered tools as `(name, description, input_schema)`
+    pub async fn list(&self) -> Vec<(String, String, serde_json::Value)> {
+        self.tools
+            .lock()
+            .await
+            .values()
+            .map(|t| (t.name.clone(), t.description.clone(), t.input_schema.clone()))
+            .collect()
+    }
+
+    /// Invoke a registered tool on the connection that contributed it
+    pub async fn call(&self, name: &str, arguments: serde_json::Value) -> anyhow::Result<String> {
+        let tool = self
+            .tools
+            .lock()
+            .await
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("no tool registered as '{}'", name))?;
+        tool.connection.call_tool(&tool.name, arguments).await
+    }
+}
+
+/// Run the IPC accept loop until the process exits
+#[cfg(unix)]
+pub async fn serve(registry: Arc<ToolRegistry>) -> std::io::Result<()> {
+    let path = std::env::temp_dir().join(SOCKET_NAME);
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path)?;
+
+    // The socket is created world-discoverable under the shared temp dir
+    // by default; RegisterTool/ExecuteAction let any connecting process
+    // inject tools or trigger system actions, so restrict it to this
+    // user rather than leaving it reachable from every local account.
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    }
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let registry = registry.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, registry).await {
+                tracing::warn!("IPC connection ended: {}", e);
+            }
+        });
+    }
+}
+
+/// Run the IPC accept loop until the process exits
+#[cfg(windows)]
+pub async fn serve(registry: Arc<ToolRegistry>) -> std::io::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut first_instance = true;
+    loop {
+        let server = if first_instance {
+            first_instance = false;
+            ServerOptions::new()
+                .first_pipe_instance(true)
+                .create(PIPE_NAME)?
+        } else {
+            ServerOptions::new().create(PIPE_NAME)?
+        };
+        server.connect().await?;
+
+        let registry = registry.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(server, registry).await {
+                tracing::warn!("IPC connection ended: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection<S>(stream: S, registry: Arc<ToolRegistry>) -> anyhow::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (mut read_half, mut write_half) = tokio::io::split(stream);
+    let (tx, mut rx) = mpsc::channel::<Vec<u8>>(32);
+
+    let writer_task = tokio::spawn(async move {
+        while let Some(frame) = rx.recv().await {
+            if write_frame(&mut write_half, &frame).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let outbound = Arc::new(OutboundConnection {
+        sender: tx.clone(),
+        next_id: AtomicU64::new(1),
+        pending: Mutex::new(HashMap::new()),
+    });
+
+    loop {
+        let frame = match read_frame(&mut read_half).await? {
+            Some(f) => f,
+            None => break,
+        };
+
+        // A frame is either a request addressed to us, or the reply to a
+        // CallTool request we issued earlier — try the reply path first.
+        if let Ok(response) = serde_json::from_slice::<IpcResponse>(&frame) {
+            let mut pending = outbound.pending.lock().await;
+            if let Some(tx) = pending.remove(&response.id) {
+                let _ = tx.send(response.result);
+                continue;
+            }
+        }
+
+        let request: IpcRequest = serde_json::from_slice(&frame)?;
+        let result = dispatch(request.method, &registry, &outbound).await;
+        let response = IpcResponse {
+            id: request.id,
+            result,
+        };
+        let bytes = serde_json::to_vec(&response)?;
+        if tx.send(bytes).await.is_err() {
+            break;
+        }
+    }
+
+    drop(tx);
+    let _ = writer_task.await;
+    Ok(())
+}
+
+async fn dispatch(
+    method: IpcMethod,
+    registry: &Arc<ToolRegistry>,
+    outbound: &Arc<OutboundConnection>,
+) -> IpcResult {
+    match method {
+        IpcMethod::GetStatus => IpcResult::Status(crate::status::read_status().await),
+        IpcMethod::ExecuteAction { action } => {
+            IpcResult::ActionResult(crate::status::execute_action(&action).await)
+        }
+        IpcMethod::ListWindows => {
+            #[cfg(windows)]
+            let names = crate::windows::list_windows()
+                .into_iter()
+                .map(|w| w.title)
+                .collect();
+            #[cfg(not(windows))]
+            let names = Vec::new();
+            IpcResult::Windows(names)
+        }
+        IpcMethod::RegisterTool {
+            name,
+            description,
+            input_schema,
+        } => {
+            registry
+                .register(RegisteredTool {
+                    name,
+                    description,
+                    input_schema,
+                    connection: outbound.clone(),
+                })
+                .await;
+            IpcResult::ToolRegistered
+        }
+        IpcMethod::CallTool { .. } => {
+            IpcResult::Error("CallTool is only valid as an outbound request".into())
+        }
+    }
+}
+
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<Option<Vec<u8>>> {
+    let mut len_buf = [0u8; 4];
+    match reader.read_exact(&mut len_buf).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+
+    let len = u32::from_le_bytes(len_buf) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("frame length {} exceeds max of {} bytes", len, MAX_FRAME_LEN),
+        ));
+    }
+    let mut buf = vec![0u8; len];
+    reader.read_exact(&mut buf).await?;
+    Ok(Some(buf))
+}
+
+async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, payload: &[u8]) -> std::io::Result<()> {
+    writer.write_all(&(payload.len() as u32).to_le_bytes()).await?;
+    writer.write_all(payload).await?;
+    writer.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn read_frame_round_trips_a_written_frame() {
+        let mut buf = Vec::new();
+        write_frame(&mut buf, b"hello").await.unwrap();
+
+        let mut cursor = std::io::Cursor::new(buf);
+        let frame = read_frame(&mut cursor).await.unwrap();
+        assert_eq!(frame, Some(b"hello".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn read_frame_returns_none_on_clean_eof() {
+        let mut cursor = std::io::Cursor::new(Vec::<u8>::new());
+        let frame = read_frame(&mut cursor).await.unwrap();
+        assert_eq!(frame, None);
+    }
+
+    #[tokio::test]
+    async fn read_frame_rejects_a_length_prefix_over_the_cap() {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&((MAX_FRAME_LEN + 1) as u32).to_le_bytes());
+
+        let mut cursor = std::io::Cursor::new(buf);
+        let err = read_frame(&mut cursor).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Reachability of the AI backends the shell can route prompts to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct BackendReachability {
+    pub claude_reachable: bool,
+    pub ollama_reachable: bool,
+}
+
+impl BackendReachability {
+    /// Neither backend is reachable
+    pub fn offline(&self) -> bool {
+        !self.claude_reachable && !self.ollama_reachable
+    }
+}
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+/// `read_status` is polled once a second, but there's no reason to hit the
+/// live Claude API that often — re-probe at most this frequently and
+/// return the cached result the rest of the time.
+const PROBE_INTERVAL: Duration = Duration::from_secs(20);
+const CLAUDE_PROBE_URL: &str = "https://api.anthropic.com";
+const OLLAMA_PROBE_URL: &str = "http://localhost:11434/api/tags";
+
+static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+static LAST_PROBE: OnceLock<Mutex<Option<(Instant, BackendReachability)>>> = OnceLock::new();
+
+fn http_client() -> &'static reqwest::Client {
+    HTTP_CLIENT.get_or_init(reqwest::Client::new)
+}
+
+/// Probe Claude's API and the local Ollama endpoint for reachability. Each
+/// probe is a short-timeout HEAD request — it only needs to prove a TCP/TLS
+/// handshake succeeds, not that the endpoint is fully functional. Probes
+/// are throttled to `PROBE_INTERVAL` and share one `reqwest::Client`, so
+/// frequent callers just get the last cached result.
+pub async fn probe_backends() -> BackendReachability {
+    let cache = LAST_PROBE.get_or_init(|| Mutex::new(None));
+    let mut cache = cache.lock().await;
+
+    if let Some((checked_at, result)) = *cache {
+        if checked_at.elapsed() < PROBE_INTERVAL {
+            return result;
+        }
+    }
+
+    let (claude_reachable, ollama_reachable) =
+        tokio::join!(probe(CLAUDE_PROBE_URL), probe(OLLAMA_PROBE_URL));
+
+    let result = BackendReachability {
+        claude_reachable,
+        ollama_reachable,
+    };
+    *cache = Some((Instant::now(), result));
+    result
+}
+
+async fn probe(url: &str) -> bool {
+    http_client()
+        .head(url)
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await
+        .is_ok()
+}
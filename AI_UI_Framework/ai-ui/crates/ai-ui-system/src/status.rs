@@ -6,11 +6,14 @@ pub struct SystemStatus {
     pub battery_charging: bool,
     pub wifi_connected: bool,
     pub wifi_ssid: Option<String>,
+    pub bluetooth_enabled: bool,
     pub volume_percent: Option<f32>,
     pub cpu_usage: f32,
     pub memory_used_gb: f32,
     pub memory_total_gb: f32,
     pub time: String,
+    pub claude_reachable: bool,
+    pub ollama_reachable: bool,
 }
 
 /// Read current system status (battery, CPU, memory, time)
@@ -33,8 +36,12 @@ pub async fn read_status() -> SystemStatus {
     #[cfg(windows)]
     {
         read_wifi_windows(&mut status);
+        read_radios_windows(&mut status).await;
     }
 
+    // No real radio query on Linux yet — same placeholder the taskbar has
+    // always shown here, just no longer dropped as a side effect of the
+    // Windows-only WinRT Radios wiring above.
     #[cfg(target_os = "linux")]
     {
         status.wifi_connected = true;
@@ -43,6 +50,12 @@ pub async fn read_status() -> SystemStatus {
     // Time
     status.time = chrono::Local::now().format("%H:%M").to_string();
 
+    // AI backend reachability, so the shell can route around an
+    // unreachable Claude API without waiting on a request to time out
+    let reachability = crate::network::probe_backends().await;
+    status.claude_reachable = reachability.claude_reachable;
+    status.ollama_reachable = reachability.ollama_reachable;
+
     status
 }
 
@@ -79,30 +92,72 @@ fn read_wifi_windows(status: &mut SystemStatus) {
     }
 }
 
-/// Execute a system action (volume, brightness, etc.)
-pub async fn execute_action(action: &str) -> String {
-    match action {
-        #[cfg(windows)]
-        "volume_up" => {
-            let _ = std::process::Command::new("powershell")
-                .args(["-Command", "(New-Object -ComObject WScript.Shell).SendKeys([char]175)"])
-                .output();
-            "Volume increased".into()
+/// Overlay the true Wi-Fi/Bluetooth radio state onto `status`, replacing the
+/// `netsh` string-matched guess with the WinRT Radios API. Falls back to
+/// leaving the `netsh`-derived values alone if radio access is denied.
+#[cfg(windows)]
+async fn read_radios_windows(status: &mut SystemStatus) {
+    use crate::connectivity::radios::RadioType;
+
+    match crate::connectivity::radios::list_radios().await {
+        Ok(radios) => {
+            for (_, info) in radios {
+                match info.kind {
+                    RadioType::WiFi => status.wifi_connected = info.on,
+                    RadioType::Bluetooth => status.bluetooth_enabled = info.on,
+                    _ => {}
+                }
+            }
         }
-        #[cfg(windows)]
-        "volume_down" => {
-            let _ = std::process::Command::new("powershell")
-                .args(["-Command", "(New-Object -ComObject WScript.Shell).SendKeys([char]174)"])
-                .output();
-            "Volume decreased".into()
+        Err(e) => {
+            tracing::warn!("Could not read radio state: {}", e);
         }
-        #[cfg(windows)]
-        "mute" => {
-            let _ = std::process::Command::new("powershell")
-                .args(["-Command", "(New-Object -ComObject WScript.Shell).SendKeys([char]173)"])
-                .output();
-            "Volume muted/unmuted".into()
+    }
+}
+
+/// Execute a system action (volume, brightness, etc.)
+pub async fn execute_action(action: &str) -> String {
+    use crate::input::InputAction;
+
+    #[cfg(windows)]
+    {
+        use crate::connectivity::radios::RadioType;
+
+        match action {
+            "wifi_toggle" => {
+                return match crate::connectivity::radios::toggle(RadioType::WiFi).await {
+                    Ok(on) => format!("Wi-Fi turned {}", if on { "on" } else { "off" }),
+                    Err(e) => format!("Failed to toggle Wi-Fi: {}", e),
+                };
+            }
+            "bluetooth_toggle" => {
+                return match crate::connectivity::radios::toggle(RadioType::Bluetooth).await {
+                    Ok(on) => format!("Bluetooth turned {}", if on { "on" } else { "off" }),
+                    Err(e) => format!("Failed to toggle Bluetooth: {}", e),
+                };
+            }
+            _ => {}
         }
-        _ => format!("Action '{}' not implemented for this platform", action),
     }
+
+    let input_action = match action {
+        "volume_up" => Some(InputAction::VolumeUp),
+        "volume_down" => Some(InputAction::VolumeDown),
+        "mute" => Some(InputAction::Mute),
+        "media_play_pause" => Some(InputAction::MediaPlayPause),
+        "media_next" => Some(InputAction::MediaNext),
+        "media_previous" => Some(InputAction::MediaPrevious),
+        "brightness_up" => Some(InputAction::BrightnessUp),
+        "brightness_down" => Some(InputAction::BrightnessDown),
+        _ => None,
+    };
+
+    if let Some(input_action) = input_action {
+        return match crate::input::backend().dispatch(input_action) {
+            Ok(message) => message,
+            Err(e) => format!("Failed to execute '{}': {}", action, e),
+        };
+    }
+
+    format!("Action '{}' not implemented for this platform", action)
 }